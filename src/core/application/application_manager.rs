@@ -8,12 +8,10 @@ use std::{
         RefMut
     },
     time::Instant,
-    time::Duration,
     cmp::Ordering,
     ops::AddAssign,
 };
 use std::ops::DerefMut;
-use std::borrow::Borrow;
 
 
 
@@ -32,6 +30,17 @@ use crate::core::{
 use crate::core::application::{
     ApplicationState,
     ApplicationIdleState,
+    StateTransitions,
+    Time,
+    application_states::StateTransition,
+};
+use crate::core::input::CursorGrab;
+use crate::core::plugins::{
+    Plugin,
+    TerrainPlugin,
+    LightingPlugin,
+    PhysicsPlugin,
+    UiPlugin,
 };
 
 
@@ -40,8 +49,8 @@ use winit::{
     event::{
         Event,
         WindowEvent,
+        DeviceEvent,
         KeyboardInput,
-        ElementState,
     },
     event_loop::{
         EventLoop,
@@ -51,7 +60,6 @@ use winit::{
 
 // egui
 
-use crate::core::plugins::components::AppInterfaceFlag;
 
 
 // logging
@@ -70,7 +78,23 @@ pub struct Application{
     input_manager: Option<RefCell<InputManager>>,
     event_loop: Option<EventLoop<()>>,
     surface: Option<Arc<vulkano::swapchain::Surface<winit::window::Window>>>,
-    state: Box<dyn ApplicationState>,
+    // state stack, the last state is the one running
+    states: Vec<Box<dyn ApplicationState>>,
+    // plugins added before startup. handed to the scene manager once it exists
+    plugins: Vec<Box<dyn Plugin>>,
+
+    // set when running without a window. dimensions of the offscreen image and how many frames to draw
+    headless_dimensions: Option<[u32; 2]>,
+    headless_frame_count: u32,
+
+    // fixed timestep. the simulation ticks at ticks_per_second regardless of frame rate, catching up at most
+    // max_frame_skip ticks per frame when rendering falls behind
+    ticks_per_second: u32,
+    max_frame_skip: u32,
+    time: Time,
+
+    // whether the window currently has the cursor grabbed, kept in sync with the scene's CursorGrab
+    cursor_grabbed: bool,
 
     log_level: LevelFilter,
     start_instant: Instant,
@@ -90,27 +114,28 @@ impl Manager for Application{
 
         // initialize other managers
         log::info!("Running manager startup functions ...");
-        let (event_loop, surface) = render_manager.startup();
+        match self.headless_dimensions {
+            Some(dimensions) => render_manager.startup_headless(dimensions),
+            None => {
+                let (event_loop, surface) = render_manager.startup();
+                self.event_loop = Some(event_loop);
+                self.surface = Some(surface);
+            }
+        }
         scene_manager.startup();
         input_manager.startup();
+        for plugin in self.plugins.drain(..) {
+            scene_manager.add_plugin(plugin);
+        }
 
-        // set to idle state
-        log::info!("Setting application idle state ...");
-        let state: &(dyn ApplicationState) = self.state.borrow();
-        scene_manager.load_scene_interface(state.scene_interface_path());
-        
         // store managers and other created things
         self.render_manager = Some(RefCell::new(render_manager));
         self.scene_manager = Some(RefCell::new(scene_manager));
         self.input_manager = Some(RefCell::new(input_manager));
-        self.event_loop = Some(event_loop);
-        self.surface = Some(surface);
 
-        // prep staged scene
-        log::info!("Prepping and activating idle scene ...");
-        self.prep_staged_scene();
-        self.temp_prep(); // here until i fix serialization again and actually have a functional editor state
-        self.activate_staged_scene();
+        // set to idle state. this stages, preps and activates the idle scene
+        log::info!("Setting application idle state ...");
+        self.push_state(Box::new(ApplicationIdleState::create()));
 
         log::info!("Startup complete...");
     }
@@ -152,16 +177,77 @@ impl Manager for Application{
 impl Application{
     // called by the client when they want to create an application
     pub fn create_application(log_level: Option<LevelFilter>) -> Self{
-        Self {
+        let mut application = Self {
             render_manager: None,
             scene_manager: None,
             input_manager: None,
             event_loop: None,
             surface: None,
+            headless_dimensions: None,
+            headless_frame_count: 0,
+            ticks_per_second: 25,
+            max_frame_skip: 5,
+            time: Time::new(25),
+            cursor_grabbed: false,
             log_level: log_level.unwrap_or(LevelFilter::Info),
             start_instant: Instant::now(),
-            state: Box::new(ApplicationIdleState::create()),
+            states: Vec::new(),
+            plugins: Vec::new(),
+        };
+
+        // built in plugins
+        application
+            .add_plugin(TerrainPlugin)
+            .add_plugin(LightingPlugin)
+            .add_plugin(PhysicsPlugin)
+            .add_plugin(UiPlugin);
+        application
+    }
+
+    // plugins should be added before startup. adding one afterwards only affects scenes staged from then on
+    pub fn add_plugin<P: Plugin + 'static>(&mut self, plugin: P) -> &mut Self {
+        match &self.scene_manager {
+            Some(manager) => manager.borrow_mut().add_plugin(Box::new(plugin)),
+            None => self.plugins.push(Box::new(plugin)),
+        }
+        self
+    }
+
+    // creates an application that renders into an offscreen image instead of a window. run() will
+    // draw frame_count frames and then shut down, no event loop or display required.
+    pub fn create_headless_application(log_level: Option<LevelFilter>, dimensions: [u32; 2], frame_count: u32) -> Self{
+        let mut application = Application::create_application(log_level);
+        application.headless_dimensions = Some(dimensions);
+        application.headless_frame_count = frame_count;
+        application
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless_dimensions.is_some()
+    }
+
+    // should be set before run. resets the tick count and elapsed time
+    pub fn set_ticks_per_second(&mut self, ticks_per_second: u32) -> &mut Self {
+        if ticks_per_second == 0 {
+            log::error!("Ticks per second has to be greater than 0, keeping {}.", self.ticks_per_second);
+            return self;
         }
+        self.ticks_per_second = ticks_per_second;
+        self.time = Time::new(ticks_per_second);
+        self
+    }
+
+    pub fn set_max_frame_skip(&mut self, max_frame_skip: u32) -> &mut Self {
+        self.max_frame_skip = max_frame_skip;
+        self
+    }
+
+    pub fn ticks_per_second(&self) -> u32 {
+        self.ticks_per_second
+    }
+
+    pub fn time(&self) -> Time {
+        self.time
     }
 
     // preps a staged scene
@@ -170,6 +256,10 @@ impl Application{
         let mut scene_manager = self.get_scene_manager().unwrap();
         let mut _scene = scene_manager.get_staged_scene().unwrap();
         let scene = _scene.deref_mut();
+        // coming back to a scene that was set aside, everything the managers insert is still there
+        if scene.activated {
+            return;
+        }
 
         match &self.input_manager {
             Some(manager) => manager.borrow_mut().prep_staged_scene(scene.borrow_mut()),
@@ -179,74 +269,162 @@ impl Application{
             Some(manager) => manager.borrow_mut().prep_staged_scene(scene.borrow_mut()),
             None => log::error!("No render manager to prep scene."),
         }
+        scene.insert_resource(StateTransitions::new());
+        scene.insert_resource(self.time);
+    }
+
+    // pushes a state onto the stack. the current state is paused underneath it
+    pub fn push_state(&mut self, mut state: Box<dyn ApplicationState>){
+        log::info!("Pushing application state {}...", state.name());
+        {
+            let mut scene_manager = self.scene_manager.as_ref().expect("No scene manager to push state with.").borrow_mut();
+            if let Some(current) = self.states.last_mut() {
+                current.on_pause(&mut scene_manager);
+            }
+            state.init_schedule();
+            state.on_enter(&mut scene_manager);
+        }
+        self.states.push(state);
+        self.activate_staged_scene_if_any();
+    }
+
+    // pops the current state and resumes the one underneath. the last state can't be popped
+    pub fn pop_state(&mut self){
+        if self.states.len() <= 1 {
+            log::warn!("Can't pop the last application state.");
+            return;
+        }
+        let mut state = self.states.pop().unwrap();
+        log::info!("Popping application state {}...", state.name());
+        {
+            let mut scene_manager = self.scene_manager.as_ref().expect("No scene manager to pop state with.").borrow_mut();
+            state.on_exit(&mut scene_manager);
+            if let Some(current) = self.states.last_mut() {
+                current.on_resume(&mut scene_manager);
+            }
+        }
+        self.activate_staged_scene_if_any();
     }
 
-    fn temp_prep(&mut self){
-        use crate::core::plugins::components::{
-            DebugUiComponent,
-            TerrainUiComponent,
-            TerrainComponent,
-            TransformComponent,
-            DirectionalLightComponent,
-            CameraComponent,
-            InputComponent,
-            GeometryType,
-            GeometryComponent,
-            RenderableComponent,
-            AmbientLightingComponent,
+    // replaces the current state
+    pub fn switch_state(&mut self, mut state: Box<dyn ApplicationState>){
+        log::info!("Switching to application state {}...", state.name());
+        {
+            let mut scene_manager = self.scene_manager.as_ref().expect("No scene manager to switch state with.").borrow_mut();
+            if let Some(mut current) = self.states.pop() {
+                current.on_exit(&mut scene_manager);
+            }
+            state.init_schedule();
+            state.on_enter(&mut scene_manager);
+        }
+        self.states.push(state);
+        self.activate_staged_scene_if_any();
+    }
+
+    // state hooks stage scenes, the managers need to prep them before they can be activated
+    fn activate_staged_scene_if_any(&mut self){
+        let has_staged_scene = self.get_scene_manager().unwrap().has_staged_scene();
+        if has_staged_scene {
+            log::info!("Prepping and activating staged scene ...");
+            self.prep_staged_scene();
+            self.activate_staged_scene();
+        }
+    }
+
+    // applies transitions systems requested during the last tick or frame
+    fn process_state_transitions(&mut self){
+        let transitions = {
+            let scene_manager = self.get_scene_manager().unwrap();
+            let mut scene = scene_manager.get_active_scene().unwrap();
+            let mut world = scene.get_world().unwrap();
+            let transitions = match world.get_resource_mut::<StateTransitions>() {
+                Some(mut transitions) => transitions.drain(),
+                None => Vec::new(),
+            };
+            transitions
         };
-        use cgmath::Vector3;
+        for transition in transitions {
+            match transition {
+                StateTransition::Push(state) => self.push_state(state),
+                StateTransition::Pop => self.pop_state(),
+                StateTransition::Switch(state) => self.switch_state(state),
+            }
+        }
+    }
 
-        let mut scene_manager = self.get_scene_manager().unwrap();
-        let mut _scene = scene_manager.get_staged_scene().unwrap();
-        let scene = _scene.deref_mut();
+    // one fixed update tick of the active scene and the current state
+    fn run_tick(&mut self){
+        let scene_manager = self.scene_manager.as_ref().expect("No scene manager to tick.").borrow_mut();
+        let mut active_scene = scene_manager.get_active_scene().unwrap();
 
-        scene.get_world()
-            .unwrap()
-            .spawn()
-            .insert(AppInterfaceFlag{})
-            .insert(DebugUiComponent::create())
-            // .marked::<SimpleMarker<SerializerFlag>>()
-            .id();
-
-        scene.get_world()
-            .unwrap()
-            .spawn()
-            .insert(TerrainComponent::create(20))
-            .insert(TransformComponent::create_empty())
-            .insert(TerrainUiComponent{})
-            .id();
-
-        scene.get_world()
-            .unwrap()
-            .spawn()
-            .insert(RenderableComponent::create())
-            .insert(GeometryComponent::create(GeometryType::Box))
-            .insert(TransformComponent::create_empty())
-            .id();
-
-        scene.get_world()
-            .unwrap()
-            .spawn()
-            .insert(DirectionalLightComponent::new(Vector3::new(-0.5, -0.2, -0.8), [1.0, 1.0, 1.0]))
-            .id();
-
-        scene.get_world()
-            .unwrap()
-            .spawn()
-            .insert(AmbientLightingComponent::new([1.0, 1.0, 1.0]))
-            .id();
-
-        scene.get_world()
-            .unwrap()
-            .spawn()
-            .insert(CameraComponent::create_default())
-            .insert(InputComponent::create())
-            .id();
+        // run input
+        self.get_input_manager().unwrap().update(&mut active_scene);
+
+        // run physics, unless the current state has the scene stopped. time only moves forward when it runs
+        let state = self.states.last_mut().expect("No application state to tick.");
+        if state.updates_scene() {
+            active_scene.insert_resource(self.time);
+            active_scene.run_update_schedule();
+            self.time.advance();
+        }
+        active_scene.insert_resource(self.time);
+        state.run_schedule(&mut active_scene);
+    }
+
+    // stopped scenes don't get snapshotted, so there's nothing to blend between
+    fn set_frame_time(&mut self, interpolation: f32, frame_delta: f32){
+        let updates_scene = self.states.last().map(|state| state.updates_scene()).unwrap_or(false);
+        self.time.interpolation = if updates_scene { interpolation.clamp(0.0, 1.0) } else { 1.0 };
+        self.time.frame_delta = frame_delta;
+        let scene_manager = self.scene_manager.as_ref().expect("No scene manager to set time on.").borrow_mut();
+        let mut active_scene = scene_manager.get_active_scene().unwrap();
+        active_scene.insert_resource(self.time);
+    }
+
+    // systems ask for the cursor to be grabbed through the CursorGrab resource
+    pub fn set_cursor_grab(&mut self, grabbed: bool){
+        {
+            let scene_manager = self.scene_manager.as_ref().expect("No scene manager to grab the cursor with.").borrow_mut();
+            let mut active_scene = scene_manager.get_active_scene().unwrap();
+            active_scene.insert_resource(CursorGrab{grabbed: grabbed});
+        }
+        self.apply_cursor_grab();
+    }
+
+    // grabs or releases the cursor if the active scene changed its mind
+    fn apply_cursor_grab(&mut self){
+        let grabbed = {
+            let scene_manager = self.scene_manager.as_ref().expect("No scene manager to grab the cursor with.").borrow_mut();
+            let mut active_scene = scene_manager.get_active_scene().unwrap();
+            let world = active_scene.get_world().unwrap();
+            let grabbed = match world.get_resource::<CursorGrab>() {
+                Some(grab) => grab.grabbed,
+                None => false,
+            };
+            grabbed
+        };
+        if grabbed == self.cursor_grabbed {
+            return;
+        }
+        if let Some(surface) = &self.surface {
+            let window = surface.window();
+            match window.set_cursor_grab(grabbed) {
+                Ok(()) => {
+                    window.set_cursor_visible(!grabbed);
+                    self.cursor_grabbed = grabbed;
+                },
+                Err(e) => log::error!("Couldn't set cursor grab to {}: {}", grabbed, e),
+            }
+        }
     }
 
     // main game loop
     pub fn run(mut self) {
+        if self.is_headless() {
+            self.run_headless();
+            return;
+        }
+
         log::info!("Running the application...");
         let event_loop = self.event_loop.take().unwrap();
 
@@ -254,30 +432,30 @@ impl Application{
         log::info!("Startup time: {:?}", Instant::now().duration_since(self.start_instant));
 
         self.start_instant = Instant::now();
-        let ticks_per_second = 25;
-        let skip_ticks = 1000 / ticks_per_second; // tick every 40 ms
-        let max_frame_skip = 5;
-        let _interpolation: f32 = 0.0;
+        log::info!("Ticking {} times per second, skipping at most {} frames.", self.ticks_per_second, self.max_frame_skip);
+        let skip_ticks = self.time.tick_duration();
+        let max_frame_skip = self.max_frame_skip;
         let mut next_tick = Instant::now();
+        let mut last_frame = Instant::now();
 
         event_loop.run(move |event, _, control_flow| {
 
             let mut loops = 0;
             while (Instant::now().cmp(&next_tick) == Ordering::Greater) && loops < max_frame_skip {
-                // get scene
-                let scene_manager = self.get_scene_manager().unwrap();
-                let mut active_scene = scene_manager.get_active_scene().unwrap();
-
-                // run input 
-                self.get_input_manager().unwrap().update(active_scene.borrow_mut());
-                
-                // run physics
-                active_scene.run_update_schedule();
+                self.run_tick();
+                self.process_state_transitions();
 
-                next_tick.add_assign(Duration::from_millis(skip_ticks));
+                next_tick.add_assign(skip_ticks);
                 loops = loops + 1;
             }
 
+            // too far behind to catch up, drop the ticks instead of spiraling
+            let now = Instant::now();
+            if now > next_tick + skip_ticks {
+                next_tick = now;
+            }
+            self.apply_cursor_grab();
+
             // pass events to egui
             let egui_consumed_event = {
                 let scene_manager = self.get_scene_manager().unwrap();
@@ -307,13 +485,45 @@ impl Application{
             match event{
                 Event::MainEventsCleared => {
                     puffin::GlobalProfiler::lock().new_frame();
+                    // how far we are between the last tick and the next one
+                    let until_next_tick = next_tick.saturating_duration_since(Instant::now());
+                    let interpolation = 1.0 - until_next_tick.as_secs_f32() / skip_ticks.as_secs_f32();
+                    let now = Instant::now();
+                    self.set_frame_time(interpolation, now.duration_since(last_frame).as_secs_f32());
+                    last_frame = now;
                     self.render_scene();
+                    self.process_state_transitions();
+                    self.apply_cursor_grab();
                 },
                 _ => (),
             }
         }); // end of event_loop run
     } // end of run function
 
+    // headless loop. one update tick and one draw per frame, then shutdown. every frame is exactly one tick so
+    // the output doesn't depend on how fast it renders
+    fn run_headless(&mut self) {
+        let frame_count = self.headless_frame_count;
+        log::info!("Running the application headless for {} frames...", frame_count);
+        log::info!("Startup time: {:?}", Instant::now().duration_since(self.start_instant));
+        self.start_instant = Instant::now();
+
+        for frame in 0..frame_count {
+            self.run_tick();
+            self.process_state_transitions();
+
+            puffin::GlobalProfiler::lock().new_frame();
+            let delta = self.time.delta;
+            self.set_frame_time(1.0, delta);
+            self.render_scene();
+            self.process_state_transitions();
+            log::debug!("Finished headless frame {}.", frame);
+        }
+
+        log::info!("Ran {} headless frames in {:?}", frame_count, Instant::now().duration_since(self.start_instant));
+        self.shutdown();
+    }
+
     fn handle_event(
         &mut self,
         event: &winit::event::Event<()>,
@@ -342,21 +552,58 @@ impl Application{
                             }
                         }
 
-                        // keyboard input
+                        // keyboard input, presses and releases
                         WindowEvent::KeyboardInput {
                             input:
                                 KeyboardInput {
                                     virtual_keycode: Some(virtual_code),
-                                    state: ElementState::Pressed,
+                                    state,
                                     ..
                                 },
                             ..
                             } => {
                                 match &self.input_manager {
-                                    Some(manager) => manager.borrow_mut().handle_key_input(Some(virtual_code.clone())),
+                                    Some(manager) => manager.borrow_mut().handle_key_input(Some(virtual_code.clone()), *state),
                                     None => log::error!("Key detected, but no input manager is loaded..."),
                                 };
                         }
+
+                        // mouse
+                        WindowEvent::CursorMoved { position, .. } => {
+                            match &self.input_manager {
+                                Some(manager) => manager.borrow_mut().handle_cursor_moved(*position),
+                                None => log::error!("Cursor moved, but no input manager is loaded..."),
+                            };
+                        }
+
+                        WindowEvent::CursorLeft { .. } => {
+                            match &self.input_manager {
+                                Some(manager) => manager.borrow_mut().handle_cursor_left(),
+                                None => log::error!("Cursor left, but no input manager is loaded..."),
+                            };
+                        }
+
+                        WindowEvent::MouseInput { state, button, .. } => {
+                            match &self.input_manager {
+                                Some(manager) => manager.borrow_mut().handle_mouse_button(*button, *state),
+                                None => log::error!("Mouse button detected, but no input manager is loaded..."),
+                            };
+                        }
+
+                        WindowEvent::MouseWheel { delta, .. } => {
+                            match &self.input_manager {
+                                Some(manager) => manager.borrow_mut().handle_mouse_wheel(*delta),
+                                None => log::error!("Mouse wheel detected, but no input manager is loaded..."),
+                            };
+                        }
+
+                        // releases don't arrive while unfocused
+                        WindowEvent::Focused(false) => {
+                            match &self.input_manager {
+                                Some(manager) => manager.borrow_mut().release_all_keys(),
+                                None => log::error!("Focus lost, but no input manager is loaded..."),
+                            };
+                        }
                         
                         // key modifiers, alt, shift, etc
                         WindowEvent::ModifiersChanged(state) => {
@@ -370,6 +617,13 @@ impl Application{
                     }
                 }
             }
+            // raw mouse movement, used for mouse-look since it isn't clamped to the window
+            Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
+                match &self.input_manager {
+                    Some(manager) => manager.borrow_mut().handle_mouse_motion(*delta),
+                    None => log::error!("Mouse motion detected, but no input manager is loaded..."),
+                };
+            }
             _ => (), // catch all of event match
         } // end of event match
     }
@@ -387,6 +641,10 @@ impl Application{
 
         let mut _scene = scene_manager.get_staged_scene().unwrap();
         let scene = _scene.deref_mut();
+        // coming back to a scene that was set aside, everything the managers insert is still there
+        if scene.activated {
+            return;
+        }
 
         // get required egui data
         let render_manager = self.get_render_manager().unwrap();
@@ -1,64 +1,412 @@
 use crate::core::scene::{
     Scene,
-    Active
+    Active,
 };
-use crate::core::systems::ui_systems::DebugUiSystem;
 use crate::core::managers::SceneManager;
+use crate::core::plugins::components::{
+    AppInterfaceFlag,
+    DebugUiComponent,
+    TerrainUiComponent,
+    TerrainComponent,
+    TransformComponent,
+    DirectionalLightComponent,
+    CameraComponent,
+    PanZoomCameraController,
+    InputComponent,
+    GeometryType,
+    GeometryComponent,
+    RenderableComponent,
+    AmbientLightingComponent,
+    PointLightComponent,
+    RigidBody,
+    Collider,
+    VelocityComponent,
+};
+use crate::core::systems::state_systems::{
+    EditorStateInputSystem,
+    PlayStateInputSystem,
+    PauseStateInputSystem,
+};
 use bevy_ecs::{
     schedule::Stage,
+    world::World,
 };
 use bevy_ecs::prelude::Schedule;
 use bevy_ecs::prelude::SystemStage;
-use std::cell::RefMut;
+use cgmath::Vector3;
+
+use std::collections::VecDeque;
 
 
-pub trait ApplicationState{
+// states live on a stack in the application. the top state's schedule runs every tick, and its hooks are
+// called as states get pushed and popped. any scene a hook stages gets prepped and activated by the application.
+pub trait ApplicationState: Send + Sync {
+    fn name(&self) -> &'static str;
     fn run_schedule(&mut self, scene: &mut Scene<Active>);
     fn init_schedule(&mut self);
-    fn scene_interface_path(&self) -> &'static str;
+    // None for states that run on top of whatever scene is already active
+    fn scene_interface_path(&self) -> Option<&'static str>;
+
+    // pushed or switched to
+    fn on_enter(&mut self, scene_manager: &mut SceneManager);
+    // popped or switched away from
+    fn on_exit(&mut self, scene_manager: &mut SceneManager);
+    // another state was pushed on top of this one
+    fn on_pause(&mut self, _scene_manager: &mut SceneManager){
+    }
+    // the state on top of this one was popped
+    fn on_resume(&mut self, _scene_manager: &mut SceneManager){
+    }
+
+    // whether the active scene's update schedule runs while this state is on top
+    fn updates_scene(&self) -> bool {
+        true
+    }
+}
+
+pub enum StateTransition{
+    Push(Box<dyn ApplicationState>),
+    Pop,
+    Switch(Box<dyn ApplicationState>),
+}
+
+// resource systems use to request state changes. the application applies them between ticks
+#[derive(Default)]
+pub struct StateTransitions{
+    queue: VecDeque<StateTransition>,
+}
+
+impl StateTransitions{
+    pub fn new() -> Self {
+        StateTransitions{
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn push<S: ApplicationState + 'static>(&mut self, state: S){
+        self.queue.push_back(StateTransition::Push(Box::new(state)));
+    }
+
+    pub fn pop(&mut self){
+        self.queue.push_back(StateTransition::Pop);
+    }
+
+    pub fn switch<S: ApplicationState + 'static>(&mut self, state: S){
+        self.queue.push_back(StateTransition::Switch(Box::new(state)));
+    }
+
+    pub fn drain(&mut self) -> Vec<StateTransition> {
+        self.queue.drain(..).collect()
+    }
+}
+
+// the scene a state owns, and the id it got when the state last staged it
+pub struct StateScene{
+    pub interface_path: &'static str,
+    scene_id: Option<i16>,
+}
+
+impl StateScene{
+    pub fn new(interface_path: &'static str) -> Self {
+        StateScene{
+            interface_path: interface_path,
+            scene_id: None,
+        }
+    }
+
+    // swaps the active scene out for this one, loaded from its interface file
+    pub fn enter(&mut self, scene_manager: &mut SceneManager){
+        scene_manager.deactivate_active_scene();
+        // anything left from a previous visit gets replaced
+        if let Some(scene_id) = self.scene_id.take() {
+            scene_manager.unregister_scene(scene_id);
+        }
+        if !scene_manager.load_scene_interface(self.interface_path) {
+            let mut scene = scene_manager.get_staged_scene().expect("State scene wasn't staged.");
+            let mut world = scene.get_world().expect("Staged scene has no world.");
+            spawn_default_scene(&mut world);
+        }
+        self.scene_id = scene_manager.get_staged_scene_id();
+    }
+
+    // the scene and its world are gone for good after this
+    pub fn exit(&mut self, scene_manager: &mut SceneManager){
+        if self.is_active(scene_manager) {
+            scene_manager.deactivate_active_scene();
+        }
+        if let Some(scene_id) = self.scene_id.take() {
+            scene_manager.unregister_scene(scene_id);
+        }
+    }
+
+    // only restages if a state above this one swapped the scene out. the scene comes back as it was left,
+    // unsaved changes and all. it's only reloaded from its file if it's been lost somehow
+    pub fn resume(&mut self, scene_manager: &mut SceneManager){
+        if self.is_active(scene_manager) {
+            return;
+        }
+        match self.scene_id {
+            Some(scene_id) if scene_manager.does_scene_exist(&scene_id) => {
+                scene_manager.deactivate_active_scene();
+                scene_manager.stage_scene(scene_id);
+            },
+            _ => self.enter(scene_manager),
+        }
+    }
+
+    fn is_active(&self, scene_manager: &SceneManager) -> bool {
+        self.scene_id.is_some() && scene_manager.get_active_scene_id() == self.scene_id
+    }
+}
+
+fn run_state_schedule(schedule: &mut Option<Box<dyn Stage>>, scene: &mut Scene<Active>){
+    log::debug!("Running state schedule...");
+    let mut stage = schedule.take().expect("State schedule wasn't initialized.");
+    stage.run(&mut *scene.get_world().unwrap());
+    *schedule = Some(stage);
+}
+
+// what a state's scene starts as when there's no interface file for it yet
+pub fn spawn_default_scene(world: &mut World){
+    world
+        .spawn()
+        .insert(AppInterfaceFlag{})
+        .insert(DebugUiComponent::create())
+        .id();
+
+    world
+        .spawn()
+        .insert(TerrainComponent::create(20))
+        .insert(TransformComponent::create_empty())
+        .insert(TerrainUiComponent{})
+        .insert(Collider::heightfield())
+        .id();
+
+    // dropped onto the terrain once the scene starts ticking
+    world
+        .spawn()
+        .insert(RenderableComponent::create())
+        .insert(GeometryComponent::create(GeometryType::Box))
+        .insert(TransformComponent::from_translation(Vector3::new(1.0, 1.0, 3.0)))
+        .insert(RigidBody::dynamic(1.0))
+        .insert(Collider::obb(Vector3::new(0.5, 0.5, 0.5)))
+        .insert(VelocityComponent::default())
+        .id();
+
+    world
+        .spawn()
+        .insert(DirectionalLightComponent::new(Vector3::new(-0.5, -0.2, -0.8), [1.0, 1.0, 1.0]))
+        .id();
+
+    world
+        .spawn()
+        .insert(AmbientLightingComponent::new([1.0, 1.0, 1.0]))
+        .id();
+
+    world
+        .spawn()
+        .insert(PointLightComponent::new(Vector3::new(0.0, 2.0, 0.0), [1.0, 0.8, 0.6], 8.0))
+        .id();
+
+    world
+        .spawn()
+        .insert(CameraComponent::create_default())
+        .insert(PanZoomCameraController::default())
+        .insert(InputComponent::create())
+        .id();
 }
 
 pub struct ApplicationIdleState{
     pub schedule: Option<Box<dyn Stage>>,
-    pub scene_interface_path: &'static str,
-    idle_scene_id: i16,
+    pub scene: StateScene,
 }
 
 impl ApplicationIdleState{
     pub fn create() -> Self{
         ApplicationIdleState{
             schedule: None,
-            scene_interface_path: "./idle_state.ron",
-            idle_scene_id: -1,
+            scene: StateScene::new("./idle_state.ron"),
         }
     }
+}
+
+impl ApplicationState for ApplicationIdleState {
+    fn name(&self) -> &'static str {
+        "idle"
+    }
+
+    fn run_schedule(&mut self, scene: &mut Scene<Active>){
+        run_state_schedule(&mut self.schedule, scene);
+    }
+
+    fn init_schedule(&mut self){
+        let mut schedule = Schedule::default();
+        schedule.add_stage("state", SystemStage::parallel());
+        self.schedule = Some(Box::new(schedule));
+    }
 
-    fn create_idle_scene(&mut self, scene_manager: Option<RefMut<SceneManager>>){
-        if self.idle_scene_id == -1 {  // if the scene doesn't exist, create one
-            let scene_id = scene_manager.expect("Scene manager should exist here.").generate_and_register_scene();
-            self.idle_scene_id = scene_id;
+    fn scene_interface_path(&self) -> Option<&'static str>{
+        Some(self.scene.interface_path)
+    }
+
+    fn on_enter(&mut self, scene_manager: &mut SceneManager){
+        self.scene.enter(scene_manager);
+    }
+
+    fn on_exit(&mut self, scene_manager: &mut SceneManager){
+        self.scene.exit(scene_manager);
+    }
+
+    fn on_resume(&mut self, scene_manager: &mut SceneManager){
+        self.scene.resume(scene_manager);
+    }
+}
+
+// editing a scene. the simulation doesn't tick, F5 starts playing
+pub struct ApplicationEditorState{
+    pub schedule: Option<Box<dyn Stage>>,
+    pub scene: StateScene,
+}
+
+impl ApplicationEditorState{
+    pub fn create() -> Self{
+        ApplicationEditorState{
+            schedule: None,
+            scene: StateScene::new("./editor_state.ron"),
         }
     }
-   
 }
 
-impl ApplicationState for ApplicationIdleState {
+impl ApplicationState for ApplicationEditorState {
+    fn name(&self) -> &'static str {
+        "editor"
+    }
+
     fn run_schedule(&mut self, scene: &mut Scene<Active>){
-        log::info!("Running scene schedule...");
-        let mut schedule = self.schedule.take().expect("No setup schedule");
-        schedule.run(&mut *scene.get_world().unwrap());
-        self.schedule = Some(schedule);
+        run_state_schedule(&mut self.schedule, scene);
     }
 
     fn init_schedule(&mut self){
         let mut schedule = Schedule::default();
-        schedule.add_stage("draw_ui", SystemStage::parallel()
-            .with_system(DebugUiSystem)
+        schedule.add_stage("state", SystemStage::parallel()
+            .with_system(EditorStateInputSystem)
         );
         self.schedule = Some(Box::new(schedule));
     }
-    
-    fn scene_interface_path(&self) -> &'static str{
-        self.scene_interface_path
+
+    fn scene_interface_path(&self) -> Option<&'static str>{
+        Some(self.scene.interface_path)
+    }
+
+    fn on_enter(&mut self, scene_manager: &mut SceneManager){
+        self.scene.enter(scene_manager);
+    }
+
+    fn on_exit(&mut self, scene_manager: &mut SceneManager){
+        self.scene.exit(scene_manager);
+    }
+
+    fn on_resume(&mut self, scene_manager: &mut SceneManager){
+        self.scene.resume(scene_manager);
+    }
+
+    fn updates_scene(&self) -> bool {
+        false
+    }
+}
+
+// running the game. escape pauses, F5 goes back to whatever state pushed this one
+pub struct ApplicationPlayState{
+    pub schedule: Option<Box<dyn Stage>>,
+    pub scene: StateScene,
+}
+
+impl ApplicationPlayState{
+    pub fn create() -> Self{
+        ApplicationPlayState{
+            schedule: None,
+            scene: StateScene::new("./play_state.ron"),
+        }
+    }
+}
+
+impl ApplicationState for ApplicationPlayState {
+    fn name(&self) -> &'static str {
+        "play"
     }
-}
\ No newline at end of file
+
+    fn run_schedule(&mut self, scene: &mut Scene<Active>){
+        run_state_schedule(&mut self.schedule, scene);
+    }
+
+    fn init_schedule(&mut self){
+        let mut schedule = Schedule::default();
+        schedule.add_stage("state", SystemStage::parallel()
+            .with_system(PlayStateInputSystem)
+        );
+        self.schedule = Some(Box::new(schedule));
+    }
+
+    fn scene_interface_path(&self) -> Option<&'static str>{
+        Some(self.scene.interface_path)
+    }
+
+    fn on_enter(&mut self, scene_manager: &mut SceneManager){
+        self.scene.enter(scene_manager);
+    }
+
+    fn on_exit(&mut self, scene_manager: &mut SceneManager){
+        self.scene.exit(scene_manager);
+    }
+
+    fn on_resume(&mut self, scene_manager: &mut SceneManager){
+        self.scene.resume(scene_manager);
+    }
+}
+
+// sits on top of the play state and keeps its scene around, but stops it from ticking
+pub struct ApplicationPauseState{
+    pub schedule: Option<Box<dyn Stage>>,
+}
+
+impl ApplicationPauseState{
+    pub fn create() -> Self{
+        ApplicationPauseState{
+            schedule: None,
+        }
+    }
+}
+
+impl ApplicationState for ApplicationPauseState {
+    fn name(&self) -> &'static str {
+        "pause"
+    }
+
+    fn run_schedule(&mut self, scene: &mut Scene<Active>){
+        run_state_schedule(&mut self.schedule, scene);
+    }
+
+    fn init_schedule(&mut self){
+        let mut schedule = Schedule::default();
+        schedule.add_stage("state", SystemStage::parallel()
+            .with_system(PauseStateInputSystem)
+        );
+        self.schedule = Some(Box::new(schedule));
+    }
+
+    fn scene_interface_path(&self) -> Option<&'static str>{
+        None
+    }
+
+    fn on_enter(&mut self, _scene_manager: &mut SceneManager){
+        log::info!("Paused.");
+    }
+
+    fn on_exit(&mut self, _scene_manager: &mut SceneManager){
+        log::info!("Unpaused.");
+    }
+
+    fn updates_scene(&self) -> bool {
+        false
+    }
+}
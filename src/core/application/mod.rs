@@ -1,6 +1,12 @@
 pub mod application_manager;
 pub mod profiler;
 pub mod application_states;
+pub mod time;
 
 pub use application_states::ApplicationIdleState;
-pub use application_states::ApplicationState;
\ No newline at end of file
+pub use application_states::ApplicationState;
+pub use application_states::ApplicationEditorState;
+pub use application_states::ApplicationPlayState;
+pub use application_states::ApplicationPauseState;
+pub use application_states::StateTransitions;
+pub use time::Time;
\ No newline at end of file
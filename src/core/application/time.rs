@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+// fixed timestep timing, inserted into the active scene before every tick and every draw
+#[derive(Debug, Clone, Copy)]
+pub struct Time{
+    // seconds simulated by one tick. constant for a given tick rate so updates stay deterministic
+    pub delta: f32,
+    // seconds simulated so far, always tick * delta
+    pub elapsed: f64,
+    // number of ticks run
+    pub tick: u64,
+    // how far between the last tick and the next one the current frame is, 0 to 1. render systems use
+    // this to blend between the previous and current transforms
+    pub interpolation: f32,
+    // real seconds since the last frame was drawn, for things that move every frame instead of every tick
+    pub frame_delta: f32,
+}
+
+impl Time{
+    pub fn new(ticks_per_second: u32) -> Self {
+        Time{
+            delta: 1.0 / ticks_per_second as f32,
+            elapsed: 0.0,
+            tick: 0,
+            interpolation: 0.0,
+            frame_delta: 0.0,
+        }
+    }
+
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_secs_f32(self.delta)
+    }
+
+    pub fn advance(&mut self){
+        self.tick += 1;
+        self.elapsed = self.tick as f64 * self.delta as f64;
+    }
+}
@@ -0,0 +1,78 @@
+use winit::event::ModifiersState;
+
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+
+use crate::core::input::{
+    InputBindings,
+    KeyBinding,
+    KeyboardState,
+};
+
+// which bound actions fired this tick and where each axis is. rebuilt by the input manager every tick and
+// inserted into the active scene, systems should read this instead of raw keys
+#[derive(Debug, Clone, Default)]
+pub struct ActionState{
+    triggered: HashSet<String>,
+    held: HashSet<String>,
+    axes: HashMap<String, f32>,
+}
+
+impl ActionState{
+    pub fn new() -> Self {
+        ActionState::default()
+    }
+
+    // runs the keyboard through the bindings
+    pub fn from_keyboard(bindings: &InputBindings, keyboard: &KeyboardState, modifiers: &ModifiersState) -> Self {
+        let mut state = ActionState::new();
+        for (action, key_bindings) in bindings.actions.iter() {
+            if key_bindings.iter().any(|binding| keyboard.just_pressed(binding.key) && binding.modifiers.is_held(modifiers)) {
+                state.triggered.insert(action.clone());
+            }
+            if key_bindings.iter().any(|binding| is_held(binding, keyboard, modifiers)) {
+                state.held.insert(action.clone());
+            }
+        }
+        for (axis, axis_binding) in bindings.axes.iter() {
+            let mut value = 0.0;
+            if axis_binding.positive.iter().any(|binding| is_held(binding, keyboard, modifiers)) {
+                value += 1.0;
+            }
+            if axis_binding.negative.iter().any(|binding| is_held(binding, keyboard, modifiers)) {
+                value -= 1.0;
+            }
+            if value != 0.0 {
+                state.axes.insert(axis.clone(), value);
+            }
+        }
+        state
+    }
+
+    // went down this tick
+    pub fn triggered(&self, action: &str) -> bool {
+        self.triggered.contains(action)
+    }
+
+    // the input manager only rebuilds this once a tick, but frames can run several times in between. the
+    // render schedule clears the triggers after its first frame so toggles don't flip back and forth
+    pub fn clear_triggered(&mut self){
+        self.triggered.clear();
+    }
+
+    // is down, including the tick it went down on
+    pub fn held(&self, action: &str) -> bool {
+        self.held.contains(action)
+    }
+
+    // -1 to 1. 0 for axes that aren't held or aren't bound
+    pub fn axis(&self, axis: &str) -> f32 {
+        *self.axes.get(axis).unwrap_or(&0.0)
+    }
+}
+
+fn is_held(binding: &KeyBinding, keyboard: &KeyboardState, modifiers: &ModifiersState) -> bool {
+    keyboard.pressed(binding.key) && binding.modifiers.is_held(modifiers)
+}
@@ -0,0 +1,149 @@
+use winit::event::{
+    ModifiersState,
+    VirtualKeyCode,
+};
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use ron::ser::PrettyConfig;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+pub const DEFAULT_BINDINGS_PATH: &str = "./input_bindings.ron";
+
+// modifiers that have to be held for a binding to fire. ones left false are ignored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BindingModifiers{
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub logo: bool,
+}
+
+impl BindingModifiers{
+    pub fn none() -> Self {
+        BindingModifiers::default()
+    }
+
+    pub fn is_held(&self, modifiers: &ModifiersState) -> bool {
+        (!self.shift || modifiers.shift())
+            && (!self.ctrl || modifiers.ctrl())
+            && (!self.alt || modifiers.alt())
+            && (!self.logo || modifiers.logo())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyBinding{
+    pub key: VirtualKeyCode,
+    #[serde(default)]
+    pub modifiers: BindingModifiers,
+}
+
+impl KeyBinding{
+    pub fn new(key: VirtualKeyCode) -> Self {
+        KeyBinding{
+            key: key,
+            modifiers: BindingModifiers::none(),
+        }
+    }
+
+    pub fn with_modifiers(key: VirtualKeyCode, modifiers: BindingModifiers) -> Self {
+        KeyBinding{
+            key: key,
+            modifiers: modifiers,
+        }
+    }
+
+    pub fn matches(&self, key: &VirtualKeyCode, modifiers: &ModifiersState) -> bool {
+        self.key == *key && self.modifiers.is_held(modifiers)
+    }
+}
+
+// an axis goes up for its positive keys and down for its negative ones
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AxisBinding{
+    #[serde(default)]
+    pub positive: Vec<KeyBinding>,
+    #[serde(default)]
+    pub negative: Vec<KeyBinding>,
+}
+
+impl AxisBinding{
+    pub fn new(positive: VirtualKeyCode, negative: VirtualKeyCode) -> Self {
+        AxisBinding{
+            positive: vec![KeyBinding::new(positive)],
+            negative: vec![KeyBinding::new(negative)],
+        }
+    }
+}
+
+// maps named actions and axes to keys. lives on the input manager and gets saved to / loaded from a ron file
+// so controls can be rebound without touching the systems that use them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputBindings{
+    #[serde(default)]
+    pub actions: BTreeMap<String, Vec<KeyBinding>>,
+    #[serde(default)]
+    pub axes: BTreeMap<String, AxisBinding>,
+}
+
+impl InputBindings{
+    pub fn new() -> Self {
+        InputBindings::default()
+    }
+
+    // the controls the engine's own systems expect
+    pub fn default_bindings() -> Self {
+        let mut bindings = InputBindings::new();
+        bindings
+            .bind_axis("move_forward", AxisBinding::new(VirtualKeyCode::W, VirtualKeyCode::S))
+            .bind_axis("move_right", AxisBinding::new(VirtualKeyCode::D, VirtualKeyCode::A))
+            .bind_axis("move_up", AxisBinding::new(VirtualKeyCode::F, VirtualKeyCode::R))
+            .bind_axis("orbit", AxisBinding::new(VirtualKeyCode::E, VirtualKeyCode::Q))
+            .bind_action("toggle_wireframe", KeyBinding::with_modifiers(
+                VirtualKeyCode::Z,
+                BindingModifiers{shift: true, alt: true, ..BindingModifiers::none()},
+            ))
+            .bind_action("toggle_play", KeyBinding::new(VirtualKeyCode::F5))
+            .bind_action("pause", KeyBinding::new(VirtualKeyCode::Escape));
+        bindings
+    }
+
+    // adds another key for an action, an action can have as many as it wants
+    pub fn bind_action(&mut self, action: &str, binding: KeyBinding) -> &mut Self {
+        self.actions.entry(action.to_string()).or_insert_with(Vec::new).push(binding);
+        self
+    }
+
+    // replaces whatever the axis was bound to
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) -> &mut Self {
+        self.axes.insert(axis.to_string(), binding);
+        self
+    }
+
+    pub fn unbind_action(&mut self, action: &str){
+        self.actions.remove(action);
+    }
+
+    pub fn unbind_axis(&mut self, axis: &str){
+        self.axes.remove(axis);
+    }
+
+    pub fn load(path: &str) -> ron::Result<Self> {
+        let data = fs::read_to_string(path)?;
+        ron::from_str(&data)
+    }
+
+    pub fn save(&self, path: &str) -> ron::Result<()> {
+        let data = ron::ser::to_string_pretty(self, PrettyConfig::new())?;
+        fs::write(path, data)?;
+        Ok(())
+    }
+}
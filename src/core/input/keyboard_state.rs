@@ -0,0 +1,69 @@
+use winit::event::VirtualKeyCode;
+
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+
+// what the keyboard looked like at the start of the current tick. built by the input manager from the raw
+// press and release events it saw since the last tick
+#[derive(Debug, Clone, Default)]
+pub struct KeyboardState{
+    // keys that are down, and how many seconds of ticks they've been down for
+    held: HashMap<VirtualKeyCode, f32>,
+    just_pressed: HashSet<VirtualKeyCode>,
+    just_released: HashSet<VirtualKeyCode>,
+}
+
+impl KeyboardState{
+    pub fn new() -> Self {
+        KeyboardState::default()
+    }
+
+    // moves on to the next tick. keys_down is what's down right now, pressed and released are the
+    // transitions since the last tick, so a tap that started and ended between ticks isn't lost
+    pub fn advance(
+        &mut self,
+        keys_down: &HashSet<VirtualKeyCode>,
+        pressed: &HashSet<VirtualKeyCode>,
+        released: &HashSet<VirtualKeyCode>,
+        delta: f32,
+    ){
+        let mut held = HashMap::new();
+        for key in keys_down.iter() {
+            let duration = match self.held.get(key) {
+                Some(duration) => duration + delta,
+                None => 0.0,
+            };
+            held.insert(*key, duration);
+        }
+        self.held = held;
+        self.just_pressed = pressed.clone();
+        self.just_released = released.clone();
+    }
+
+    pub fn pressed(&self, key: VirtualKeyCode) -> bool {
+        self.held.contains_key(&key)
+    }
+
+    pub fn just_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.just_pressed.contains(&key)
+    }
+
+    pub fn just_released(&self, key: VirtualKeyCode) -> bool {
+        self.just_released.contains(&key)
+    }
+
+    // 0 on the tick a key goes down and for keys that aren't down
+    pub fn held_duration(&self, key: VirtualKeyCode) -> f32 {
+        *self.held.get(&key).unwrap_or(&0.0)
+    }
+
+    pub fn pressed_keys(&self) -> impl Iterator<Item = &VirtualKeyCode> {
+        self.held.keys()
+    }
+
+    pub fn just_pressed_keys(&self) -> impl Iterator<Item = &VirtualKeyCode> {
+        self.just_pressed.iter()
+    }
+}
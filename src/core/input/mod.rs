@@ -0,0 +1,13 @@
+pub mod bindings;
+pub mod action_state;
+pub mod keyboard_state;
+pub mod mouse_state;
+
+pub use bindings::InputBindings;
+pub use bindings::KeyBinding;
+pub use bindings::AxisBinding;
+pub use bindings::BindingModifiers;
+pub use action_state::ActionState;
+pub use keyboard_state::KeyboardState;
+pub use mouse_state::MouseState;
+pub use mouse_state::CursorGrab;
@@ -0,0 +1,43 @@
+use winit::event::MouseButton;
+
+use std::collections::HashSet;
+
+// the mouse as of the start of the current tick. built by the input manager like the keyboard state
+#[derive(Debug, Clone, Default)]
+pub struct MouseState{
+    // window pixels from the top left. None while the cursor is outside the window
+    pub position: Option<[f32; 2]>,
+    // wheel movement since the last tick in lines, y is positive scrolling away from the user
+    pub wheel: [f32; 2],
+    // raw device movement since the last tick. not affected by the cursor hitting the edge of the window or
+    // being grabbed, so this is what mouse-look should use
+    pub motion: [f32; 2],
+    pub buttons_down: HashSet<MouseButton>,
+    pub buttons_pressed: HashSet<MouseButton>,
+    pub buttons_released: HashSet<MouseButton>,
+}
+
+impl MouseState{
+    pub fn new() -> Self {
+        MouseState::default()
+    }
+
+    pub fn pressed(&self, button: MouseButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    pub fn just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+}
+
+// systems set this to lock and hide the cursor for mouse-look. the application applies it to the window
+// after every tick and frame, headless applications ignore it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CursorGrab{
+    pub grabbed: bool,
+}
@@ -1,9 +1,26 @@
+use winit::event::ElementState;
 use winit::event::ModifiersState;
 use winit::event::VirtualKeyCode;
+use winit::event::MouseButton;
+use winit::event::MouseScrollDelta;
+use winit::dpi::PhysicalPosition;
 use super::super::managers::manager::Manager;
-use std::collections::VecDeque;
+use std::collections::{
+    HashSet,
+    VecDeque,
+};
 
 use crate::core::scene::{Scene, Active, Staged};
+use crate::core::input::{
+    ActionState,
+    InputBindings,
+    KeyboardState,
+    MouseState,
+    CursorGrab,
+    bindings::DEFAULT_BINDINGS_PATH,
+};
+use crate::core::application::Time;
+use std::path::Path;
 
 
 
@@ -11,15 +28,32 @@ use log;
 
 pub type KeyInputQueue = VecDeque<VirtualKeyCode>;
 
+// how many pixels of touchpad scrolling count as one line of wheel scrolling
+const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+
 pub struct InputManager{
     modifier_state: ModifiersState,
     current_key_pressed: Option<VirtualKeyCode>,
     key_input_queue: VecDeque<VirtualKeyCode>,
+    bindings: InputBindings,
+
+    // raw key state from window events, turned into a KeyboardState every tick
+    keys_down: HashSet<VirtualKeyCode>,
+    keys_pressed: HashSet<VirtualKeyCode>,
+    keys_released: HashSet<VirtualKeyCode>,
+    keyboard_state: KeyboardState,
+
+    // built up from window and device events, published and reset every tick
+    mouse_state: MouseState,
 }
 
 impl Manager for InputManager{
     fn startup(&mut self){
         log::info!("Starting input manager...");
+        // the defaults stay in memory until something saves them explicitly
+        if Path::new(DEFAULT_BINDINGS_PATH).exists() {
+            self.load_bindings(DEFAULT_BINDINGS_PATH);
+        }
     }
 
     fn shutdown(&mut self){
@@ -29,9 +63,28 @@ impl Manager for InputManager{
     fn update(&mut self, scene: &mut Scene<Active>){
         log::debug!("Updating input manager.");
         self.current_key_pressed = None;
+
+        // held durations count simulated time so they line up with the tick rate
+        let delta = match scene.get_world().unwrap().get_resource::<Time>() {
+            Some(time) => time.delta,
+            None => 0.0,
+        };
+        self.keyboard_state.advance(&self.keys_down, &self.keys_pressed, &self.keys_released, delta);
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+
         scene.insert_resource(self.key_input_queue.clone());
         scene.insert_resource(self.modifier_state);
+        scene.insert_resource(self.keyboard_state.clone());
+        scene.insert_resource(ActionState::from_keyboard(&self.bindings, &self.keyboard_state, &self.modifier_state));
+        scene.insert_resource(self.mouse_state.clone());
         self.key_input_queue.clear();
+
+        // position and held buttons carry over, everything else is per tick
+        self.mouse_state.wheel = [0.0, 0.0];
+        self.mouse_state.motion = [0.0, 0.0];
+        self.mouse_state.buttons_pressed.clear();
+        self.mouse_state.buttons_released.clear();
     }
 }
 
@@ -44,6 +97,52 @@ impl InputManager {
             modifier_state: ModifiersState::empty(),
             current_key_pressed: None,
             key_input_queue: VecDeque::new(),
+            bindings: InputBindings::default_bindings(),
+            keys_down: HashSet::new(),
+            keys_pressed: HashSet::new(),
+            keys_released: HashSet::new(),
+            keyboard_state: KeyboardState::new(),
+            mouse_state: MouseState::new(),
+        }
+    }
+
+    pub fn bindings(&self) -> &InputBindings {
+        &self.bindings
+    }
+
+    pub fn bindings_mut(&mut self) -> &mut InputBindings {
+        &mut self.bindings
+    }
+
+    pub fn set_bindings(&mut self, bindings: InputBindings){
+        self.bindings = bindings;
+    }
+
+    // keeps the current bindings if the file can't be read
+    pub fn load_bindings(&mut self, path: &str) -> bool {
+        match InputBindings::load(path) {
+            Ok(bindings) => {
+                log::info!("Loaded input bindings from {}", path);
+                self.bindings = bindings;
+                true
+            },
+            Err(e) => {
+                log::error!("Couldn't load input bindings from {}: {}", path, e);
+                false
+            },
+        }
+    }
+
+    pub fn save_bindings(&self, path: &str) -> bool {
+        match self.bindings.save(path) {
+            Ok(()) => {
+                log::info!("Saved input bindings to {}", path);
+                true
+            },
+            Err(e) => {
+                log::error!("Couldn't save input bindings to {}: {}", path, e);
+                false
+            },
         }
     }
 
@@ -53,18 +152,89 @@ impl InputManager {
         self.modifier_state = new_state;
     }
 
-    // handle key input
-    pub fn handle_key_input(&mut self, key_pressed: Option<VirtualKeyCode>){
+    // handle key input. os key repeats come in as more presses, they go in the queue but don't count as new
+    // presses for the keyboard state
+    pub fn handle_key_input(&mut self, key: Option<VirtualKeyCode>, state: ElementState){
         log::debug!("Key input picked up by InputManager...");
-        match key_pressed {
-            Some(key) => self.key_input_queue.push_back(key),
-            _ => (),
+        let key = match key {
+            Some(key) => key,
+            None => return,
+        };
+        match state {
+            ElementState::Pressed => {
+                self.key_input_queue.push_back(key);
+                if self.keys_down.insert(key) {
+                    self.keys_pressed.insert(key);
+                }
+                self.current_key_pressed = Some(key);
+            },
+            ElementState::Released => {
+                if self.keys_down.remove(&key) {
+                    self.keys_released.insert(key);
+                }
+            },
+        }
+    }
+
+    // the window lost focus, it won't see the releases for anything that's down
+    pub fn release_all_keys(&mut self){
+        for key in self.keys_down.drain() {
+            self.keys_released.insert(key);
         }
-        self.current_key_pressed = key_pressed;
+        for button in self.mouse_state.buttons_down.drain() {
+            self.mouse_state.buttons_released.insert(button);
+        }
+        self.modifier_state = ModifiersState::empty();
+    }
+
+    pub fn handle_cursor_moved(&mut self, position: PhysicalPosition<f64>){
+        self.mouse_state.position = Some([position.x as f32, position.y as f32]);
+    }
+
+    pub fn handle_cursor_left(&mut self){
+        self.mouse_state.position = None;
+    }
+
+    pub fn handle_mouse_button(&mut self, button: MouseButton, state: ElementState){
+        log::debug!("Mouse button input picked up by InputManager...");
+        match state {
+            ElementState::Pressed => {
+                if self.mouse_state.buttons_down.insert(button) {
+                    self.mouse_state.buttons_pressed.insert(button);
+                }
+            },
+            ElementState::Released => {
+                if self.mouse_state.buttons_down.remove(&button) {
+                    self.mouse_state.buttons_released.insert(button);
+                }
+            },
+        }
+    }
+
+    pub fn handle_mouse_wheel(&mut self, delta: MouseScrollDelta){
+        let [x, y] = match delta {
+            MouseScrollDelta::LineDelta(x, y) => [x, y],
+            MouseScrollDelta::PixelDelta(position) => [
+                position.x as f32 / PIXELS_PER_SCROLL_LINE,
+                position.y as f32 / PIXELS_PER_SCROLL_LINE,
+            ],
+        };
+        self.mouse_state.wheel[0] += x;
+        self.mouse_state.wheel[1] += y;
+    }
+
+    // raw movement from the device, keeps coming in while the cursor is grabbed
+    pub fn handle_mouse_motion(&mut self, delta: (f64, f64)){
+        self.mouse_state.motion[0] += delta.0 as f32;
+        self.mouse_state.motion[1] += delta.1 as f32;
     }
 
     pub fn prep_staged_scene(&mut self, scene: &mut Scene<Staged>){
         scene.insert_resource(self.key_input_queue.clone());
         scene.insert_resource(self.modifier_state);
+        scene.insert_resource(self.keyboard_state.clone());
+        scene.insert_resource(ActionState::new());
+        scene.insert_resource(self.mouse_state.clone());
+        scene.insert_resource(CursorGrab::default());
     }
 }
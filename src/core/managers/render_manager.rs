@@ -2,12 +2,15 @@
 use crate::core::{
     rendering::{
         SceneState,
+        MeshAssets,
     },
     scene::{
         scene::{Scene, Active, Staged},
     },
     systems::{
         ui_systems::EguiState,
+        render_systems::CameraViews,
+        render_systems::CullingStats,
     }
 };
 
@@ -46,12 +49,14 @@ use vulkano::{
             ImageView,
         },
         ImageUsage,
+        ImageAccess,
         SwapchainImage,
         AttachmentImage
     },
     render_pass::{
         Subpass,
     },
+    format::Format,
     pipeline::{
         GraphicsPipeline,
     },
@@ -111,6 +116,9 @@ pub type DirectionalLightingPipelne = GraphicsPipeline;
 pub type AmbientLightingPipeline = GraphicsPipeline;
 pub type PointLightingPipeline = GraphicsPipeline;
 
+// format of the image headless rendering draws into
+pub const OFFSCREEN_IMAGE_FORMAT: Format = Format::R8G8B8A8_UNORM;
+
 pub struct RenderManager{
     // Vulkan
     required_extensions: Option<InstanceExtensions>,
@@ -123,6 +131,7 @@ pub struct RenderManager{
     pub recreate_swapchain: bool,
     pub previous_frame_end: Option<Box<dyn GpuFuture>>,
     pub images: Option<Vec<Arc<ImageView<SwapchainImage<winit::window::Window>>>>>,
+    pub offscreen_image: Option<Arc<ImageView<AttachmentImage>>>,
     pub scene_state: Option<Arc<SceneState>>,
 }
 
@@ -185,7 +194,7 @@ impl RenderManager{
 
         // TODO : Somehow make this aware of when scenes are Active and do this there instead.
         let mut scene_state = SceneState::new();
-        scene_state.initialize(swapchain.clone(), device.clone());
+        scene_state.initialize(swapchain.image_format(), device.clone(), queue.clone());
         scene_state.scale_scene_state_to_images(images[0].clone(), device.clone());
 
         let _recreate_swapchain = false;
@@ -209,6 +218,60 @@ impl RenderManager{
         (event_loop, return_surface)
     }
 
+    // starts the render manager without a window or swapchain. everything is drawn into an offscreen
+    // attachment image of the given dimensions instead.
+    pub fn startup_headless(&mut self, dimensions: [u32; 2]){
+        log::info!("Starting RenderManager in headless mode...");
+
+        // no surface, so no window extensions and no swapchain
+        let required_extensions = InstanceExtensions::none();
+        let device_extensions = DeviceExtensions::none();
+
+        let instance = Instance::new(
+            InstanceCreateInfo{
+                enabled_extensions: required_extensions,
+                ..Default::default()
+            }
+        ).unwrap();
+
+        let (physical_device, queue_family) = RenderManager::get_headless_physical_device_and_queue_family(
+            &instance,
+            device_extensions.clone(),
+        );
+
+        log::info!(
+            "Using device: {} (type: {:?})",
+            physical_device.properties().device_name,
+            physical_device.properties().device_type,
+        );
+
+        let (device, mut queues) = RenderManager::get_logical_device_and_queues(
+            physical_device,
+            &device_extensions,
+            queue_family
+        );
+        let queue = queues.next().unwrap();
+
+        // the image we render into in place of a swapchain image
+        let offscreen_image = RenderManager::create_offscreen_image(device.clone(), dimensions);
+
+        let mut scene_state = SceneState::new();
+        scene_state.initialize(OFFSCREEN_IMAGE_FORMAT, device.clone(), queue.clone());
+        scene_state.scale_scene_state_to_images(offscreen_image.clone(), device.clone());
+
+        let previous_frame_end = Some(sync::now(device.clone()).boxed());
+
+        self.required_extensions = Some(required_extensions);
+        self.device_extensions = Some(device_extensions);
+        self.instance = Some(instance);
+        self.device = Some(device);
+        self.queue = Some(queue);
+        self.previous_frame_end = previous_frame_end;
+        self.recreate_swapchain = false;
+        self.offscreen_image = Some(offscreen_image);
+        self.scene_state = Some(Arc::new(scene_state));
+    }
+
     // shut down render manager
     pub fn shutdown(&mut self){
         log::info!("Shutting down render manager...");
@@ -222,7 +285,6 @@ impl RenderManager{
         log::info!("Render Manager prepping scene...");
         // get required egui data
         let (egui_ctx, egui_painter) = self.initialize_egui();
-        let egui_winit = self.create_egui_winit_state();
         let egui_state = EguiState{ctx: egui_ctx, painter: egui_painter};
         let secondary_buffer_vec: TriangleSecondaryBuffers = TriangleSecondaryBuffers{buffers: Vec::new()}; 
         let lighting_buffer_vec: LightingSecondaryBuffers = LightingSecondaryBuffers{buffers: Vec::new()};
@@ -232,11 +294,17 @@ impl RenderManager{
         scene.insert_resource(lighting_buffer_vec);
         scene.insert_resource(save);
         scene.insert_resource(egui_state);
-        scene.insert_resource(egui_winit);
+        scene.insert_resource(MeshAssets::new());
+        // there's no window to pull egui input from when headless
+        if !self.is_headless() {
+            scene.insert_resource(self.create_egui_winit_state());
+            scene.insert_resource(self.surface());
+        }
         scene.insert_resource(self.device());
-        scene.insert_resource(self.surface());
         scene.insert_resource(self.queue());
         scene.insert_resource(camera_state);
+        scene.insert_resource(CameraViews::default());
+        scene.insert_resource(CullingStats::default());
         scene.insert_resource(self.scene_state());
         log::debug!("Does device exist: {:?}", scene.contains_resource::<Arc<Device>>());
     }
@@ -258,6 +326,7 @@ impl RenderManager{
             recreate_swapchain: false,
             previous_frame_end: None,
             images: None,
+            offscreen_image: None,
             scene_state: None,
         };
         render_sys
@@ -274,11 +343,21 @@ impl RenderManager{
     ){
         puffin::profile_function!();
         log::debug!("Entering draw");
+        // let go of whatever earlier frames have finished with. headless never acquires a swapchain image, so
+        // this can't wait for prep_swapchain or the chain of frames grows forever
+        self.previous_frame_end.as_mut().unwrap().cleanup_finished();
+
         // create primary command buffer builder
         let mut command_buffer_builder = self.get_auto_command_buffer_builder();
 
-        // get swapchain image num and future
-        let (image_num, acquire_future) = self.prep_swapchain();
+        // get swapchain image num and future. headless rendering always draws into the one offscreen image
+        let (image_num, acquire_future) = match self.is_headless() {
+            true => (0, None),
+            false => {
+                let (image_num, acquire_future) = self.prep_swapchain();
+                (image_num, Some(acquire_future))
+            }
+        };
 
         // begin main render pass
         log::debug!("Entering main render pass");
@@ -290,7 +369,10 @@ impl RenderManager{
         ];
 
         // scales framebuffer and attachments to swapchain image view of swapchain image
-        self.scene_state().scale_scene_state_to_images(self.images()[image_num].clone(), self.device());
+        match self.is_headless() {
+            true => self.scene_state().scale_scene_state_to_images(self.offscreen_image(), self.device()),
+            false => self.scene_state().scale_scene_state_to_images(self.images()[image_num].clone(), self.device()),
+        }
 
         // insert stuff into scene that systems will need
         let secondary_buffer_vec: TriangleSecondaryBuffers = TriangleSecondaryBuffers{buffers: Vec::new()}; 
@@ -306,8 +388,11 @@ impl RenderManager{
         {
             let mut world = scene.get_world().unwrap();
             let ctx = world.get_resource_mut::<EguiState>().expect("Couldn't get egui state.").ctx.clone();
-            let mut egui_winit = world.get_resource_mut::<egui_winit::State>().expect("Couldn't get egui winit state.");
-            ctx.begin_frame(egui_winit.take_egui_input(self.surface().window()));
+            let raw_input = match world.get_resource_mut::<egui_winit::State>() {
+                Some(mut egui_winit) => egui_winit.take_egui_input(self.surface().window()),
+                None => self.headless_egui_input(),
+            };
+            ctx.begin_frame(raw_input);
         }
 
         // run all systems. This will build secondary command buffers
@@ -331,16 +416,18 @@ impl RenderManager{
             let textures_delta = egui_output.textures_delta.clone();
 
             // let mut egui_winit = scene.get_world().unwrap().get_resource_mut::<egui_winit::State>().expect("Couldn't get egui winit state");
-            scene
-                .get_world()
-                .unwrap()
-                .get_resource_mut::<egui_winit::State>()
-                .expect("Couldn't get egui winit state")
-                .handle_platform_output(
-                    self.surface().window(),
-                    &ctx,
-                    platform_output
-                );
+            if let Some(surface) = &self.surface {
+                scene
+                    .get_world()
+                    .unwrap()
+                    .get_resource_mut::<egui_winit::State>()
+                    .expect("Couldn't get egui winit state")
+                    .handle_platform_output(
+                        surface.window(),
+                        &ctx,
+                        platform_output
+                    );
+            }
             
             scene
                 .get_world()
@@ -381,10 +468,7 @@ impl RenderManager{
 
         // add egui draws to command buffer
         {
-            let surface = self.surface();
-            let size = surface.window().inner_size();
-            let sf: f32 = surface.window().scale_factor() as f32;
-            // let sf = 1.0;
+            let screen_size = self.egui_screen_size();
             let mut world = scene.get_world().unwrap();
             let ctx = world.get_resource_mut::<EguiState>().expect("Couldn't get egui state.").ctx.clone();
             // ctx.set_pixels_per_point(1.0);
@@ -395,7 +479,7 @@ impl RenderManager{
                 .painter
                 .draw(
                     &mut command_buffer_builder,
-                    [(size.width as f32) / sf, (size.height as f32) / sf],
+                    screen_size,
                     &ctx,
                     egui_output.shapes,
                 )
@@ -412,18 +496,26 @@ impl RenderManager{
 
         // submit and render
         log::debug!("Submitting");
-        let future = self.previous_frame_end
-            .take()
-            .unwrap()
-            .join(acquire_future)
-            .then_execute(self.queue(), command_buffer)
-            .unwrap()
-            .then_swapchain_present(self.queue(), self.swapchain(), image_num)
-            .then_signal_fence_and_flush();
+        let previous_frame_end = self.previous_frame_end.take().unwrap();
+        let future = match acquire_future {
+            Some(acquire_future) => previous_frame_end
+                .join(acquire_future)
+                .then_execute(self.queue(), command_buffer)
+                .unwrap()
+                .then_swapchain_present(self.queue(), self.swapchain(), image_num)
+                .then_signal_fence_and_flush()
+                .map(|future| future.boxed()),
+            // nothing to present when headless, the frame just stays in the offscreen image
+            None => previous_frame_end
+                .then_execute(self.queue(), command_buffer)
+                .unwrap()
+                .then_signal_fence_and_flush()
+                .map(|future| future.boxed()),
+        };
 
         match future {
             Ok(future) => {
-                self.previous_frame_end = Some(future.boxed());
+                self.previous_frame_end = Some(future);
             }
             Err(FlushError::OutOfDate) => {
                 self.recreate_swapchain = true;
@@ -441,8 +533,6 @@ impl RenderManager{
         &mut self,
     )->(usize, SwapchainAcquireFuture<winit::window::Window>)
     {
-        self.previous_frame_end.as_mut().unwrap().cleanup_finished();
-
         // acquire an image from the swapchain
         let (image_num, suboptimal, acquire_future) = self.acquire_swapchain_image();
 
@@ -467,9 +557,13 @@ impl RenderManager{
         let camera_state: [Matrix4<f32>; 2] = [Matrix4::from_scale(1.0), Matrix4::from_scale(1.0)];
         // insert resources. some of these should eventually be submitted more often than othrs
         scene.insert_resource(self.device());
-        scene.insert_resource(self.surface());
+        if !self.is_headless() {
+            scene.insert_resource(self.surface());
+        }
         scene.insert_resource(self.queue());
         scene.insert_resource(camera_state);
+        scene.insert_resource(CameraViews::default());
+        scene.insert_resource(CullingStats::default());
         scene.insert_resource(self.scene_state());
     }
 
@@ -487,6 +581,25 @@ impl RenderManager{
         (required_extensions, device_extensions)
     }
 
+    // creates the color attachment image headless rendering draws into
+    pub fn create_offscreen_image(device: Arc<Device>, dimensions: [u32; 2]) -> Arc<ImageView<AttachmentImage>> {
+        let usage = ImageUsage {
+            color_attachment: true,
+            transfer_source: true,
+            ..ImageUsage::none()
+        };
+        ImageView::new_default(
+            AttachmentImage::with_usage(
+                device.clone(),
+                dimensions,
+                OFFSCREEN_IMAGE_FORMAT,
+                usage,
+            )
+            .expect("Couldn't create offscreen image."),
+        )
+        .unwrap()
+    }
+
     // creates a surface and ties it to the event loop
     pub fn create_event_loop_and_surface(instance: Arc<Instance>) -> (EventLoop<()>, Arc<vulkano::swapchain::Surface<winit::window::Window>>) {
         let event_loop = EventLoop::new();
@@ -529,6 +642,32 @@ impl RenderManager{
             (physical_device, queue_family)
     }
 
+    // gets physical GPU and queues when there's no surface to present to
+    pub fn get_headless_physical_device_and_queue_family(
+        instance: &Arc<Instance>,
+        device_extensions: DeviceExtensions,
+    ) -> (PhysicalDevice, QueueFamily) {
+        PhysicalDevice::enumerate(&instance)
+            .filter(|&p| {
+                p.supported_extensions().is_superset_of(&device_extensions)
+            })
+            .filter_map(|p| {
+                p.queue_families()
+                    .find(|&q| q.supports_graphics())
+                    .map(|q| (p, q))
+            })
+            .min_by_key(|(p, _)| { // CI machines will usually only have a Cpu device
+                match p.properties().device_type {
+                    PhysicalDeviceType::DiscreteGpu => 0,
+                    PhysicalDeviceType::IntegratedGpu => 1,
+                    PhysicalDeviceType::VirtualGpu => 2,
+                    PhysicalDeviceType::Cpu => 3,
+                    PhysicalDeviceType::Other => 4,
+                }
+            })
+            .expect("Couldn't find a physical device for headless rendering.")
+    }
+
     // create logical device and queues. Currently a very thin pass-through
     // but it's here in case i ever want to extend this
     pub fn get_logical_device_and_queues(
@@ -638,6 +777,33 @@ impl RenderManager{
         (egui_ctx, egui_painter)
     }
 
+    // egui input for a frame when there's no window feeding it events
+    fn headless_egui_input(&self) -> egui::RawInput {
+        let [width, height] = self.offscreen_image().image().dimensions().width_height();
+        egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(width as f32, height as f32),
+            )),
+            ..Default::default()
+        }
+    }
+
+    // size of the egui screen in points
+    fn egui_screen_size(&self) -> [f32; 2] {
+        match &self.surface {
+            Some(surface) => {
+                let size = surface.window().inner_size();
+                let sf: f32 = surface.window().scale_factor() as f32;
+                [(size.width as f32) / sf, (size.height as f32) / sf]
+            },
+            None => {
+                let [width, height] = self.offscreen_image().image().dimensions().width_height();
+                [width as f32, height as f32]
+            }
+        }
+    }
+
     pub fn create_egui_winit_state(&self) -> egui_winit::State{
         let surface = self.surface();
         let window = surface.window();
@@ -666,6 +832,14 @@ impl RenderManager{
         self.images.clone().unwrap().clone()
     }
 
+    pub fn offscreen_image(&self) -> Arc<ImageView<AttachmentImage>> {
+        self.offscreen_image.clone().expect("No offscreen image, render manager isn't headless.")
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.surface.is_none()
+    }
+
     pub fn scene_state(&self) -> Arc<SceneState> {
         self.scene_state.clone().unwrap().clone()
     }
@@ -5,14 +5,18 @@ use std::cell::{
 };
 use std::sync::Mutex;
 use std::path::Path;
+use std::fs;
 
 use crate::core::{
     managers::manager::Manager,
+    plugins::Plugin,
     scene::{
         Scene,
         Active,
         Inactive,
         Staged,
+        ComponentRegistry,
+        SceneInterfacePath,
     },
 };
 
@@ -23,6 +27,8 @@ pub struct SceneManager{
     staged_scene_id: Option<i16>,
     scenes: Mutex<HashMap<i16, Scene<Inactive>>>, // Scene ids and scenes
     scene_counter: i16,
+    component_registry: ComponentRegistry,
+    plugins: Vec<Box<dyn Plugin>>,
 }
 
 impl Manager for SceneManager{
@@ -51,12 +57,21 @@ impl SceneManager{
             staged_scene_id: None,
             scenes: Mutex::new(HashMap::new()),
             scene_counter: 0,
+            component_registry: ComponentRegistry::with_engine_components(),
+            plugins: Vec::new(),
         }
     }
 
     pub fn prep_staged_scene(&mut self, _scene: &mut Scene<Staged>){
     }
 
+    // plugins apply to every scene staged after they're added
+    pub fn add_plugin(&mut self, plugin: Box<dyn Plugin>){
+        log::info!("Adding plugin {}...", plugin.name());
+        plugin.register_components(&mut self.component_registry);
+        self.plugins.push(plugin);
+    }
+
     pub fn does_save_exist(&self, save_name: &'static str) -> bool {
         Path::new(save_name).exists()
     }
@@ -91,20 +106,36 @@ impl SceneManager{
         let staged_scene_id = self.staged_scene_id.take().expect("Staged scene id not set.");
         let staged_scene = self.staged_scene.take().expect("Staged scene not set.");
         log::info!("Activating staged scene {:?}...", staged_scene_id);
-        let active_scene = Scene::<Active>::from(staged_scene.into_inner());
+        let mut active_scene = Scene::<Active>::from(staged_scene.into_inner());
+        active_scene.apply_plugins(&self.plugins);
         self.active_scene = Some(RefCell::new(active_scene));
         self.active_scene_id = Some(staged_scene_id);
     }
 
+    // moves the active scene back into the registered scenes. its world is kept until the scene is unregistered
+    pub fn deactivate_active_scene(&mut self){
+        let (active_scene, active_scene_id) = match (self.active_scene.take(), self.active_scene_id.take()) {
+            (Some(scene), Some(id)) => (scene, id),
+            _ => return,
+        };
+        log::info!("Deactivating scene {:?}...", active_scene_id);
+        let inactive_scene = Scene::<Inactive>::from(active_scene.into_inner());
+        self.scenes.lock().unwrap().insert(active_scene_id, inactive_scene);
+    }
+
     pub fn stage_scene(&mut self, id: i16){
         if self.active_scene_id.is_some() {
-            log::error!("Scene {:?} does not exist to be staged.", id);
+            log::error!("Can't stage scene {:?} while scene {:?} is active.", id, self.active_scene_id);
             return;
         }
         log::info!("Staging scene {:?}...", id);
         let inactive_scene = self.scenes.lock().unwrap().remove(&id).expect("Scene does not exist");
-        let staged_scene = Some(RefCell::new(Scene::<Staged>::from(inactive_scene)));
-        self.staged_scene = staged_scene;
+        let mut staged_scene = Scene::<Staged>::from(inactive_scene);
+        // a world that's been active before already has its plugin resources
+        if !staged_scene.activated {
+            staged_scene.apply_plugins(&self.plugins);
+        }
+        self.staged_scene = Some(RefCell::new(staged_scene));
         self.staged_scene_id = Some(id);
     }
 
@@ -113,6 +144,14 @@ impl SceneManager{
         Some(scene.borrow_mut())
     }
 
+    pub fn has_staged_scene(&self) -> bool {
+        self.staged_scene.is_some()
+    }
+
+    pub fn get_staged_scene_id(&self) -> Option<i16> {
+        self.staged_scene_id.clone()
+    }
+
     pub fn stage_active_scene(&mut self){
         todo!();
     }
@@ -121,88 +160,48 @@ impl SceneManager{
         self.scenes.lock().unwrap().contains_key(id)
     }
 
-    pub fn load_scene_interface(&mut self, _interface_path: &'static str) {
-        
+    // creates and stages a new scene, filling it from the interface file if one exists. returns whether
+    // anything was loaded from disk
+    pub fn load_scene_interface(&mut self, interface_path: &'static str) -> bool {
         let scene_id = self.generate_and_register_scene();  // create scene
         self.stage_scene(scene_id);  // stage it
-    //     {
-    //         let mut scene = self.get_staged_scene().expect("should probably just not wrap this?. but anyways couldn't get scene during load.");
-
-    //         {
-    //             scene.register::<TransformComponent>();
-    //             scene.register::<TransformUiComponent>();
-    //             scene.register::<RenderableComponent>();
-    //             scene.register::<CameraComponent>();
-    //             scene.register::<InputComponent>();
-    //             scene.register::<DebugUiComponent>();
-    //             scene.register::<DirectionalLightComponent>();
-    //             scene.register::<AmbientLightingComponent>();
-    //             scene.register::<TerrainComponent>();
-    //             scene.register::<TerrainUiComponent>();
-    //             scene.register::<GeometryComponent>();
-    //             scene.register::<SimpleMarker<SerializerFlag>>();
-    //             scene.insert_resource(SimpleMarkerAllocator::<SerializerFlag>::new());
-    //         }
-
-    //         let mut world = scene.get_world().expect("couldn't get world out of scene in load from ron.");
-
-
-    //         {
-    //             // Ensure world is empty. Can probably remove this since I literally just made it?
-    //             let mut to_delete = Vec::new();
-    //             for e in world.entities().join() {
-    //                 to_delete.push(e);
-    //             }
-    //             for del in to_delete.iter() {
-    //                 world.delete_entity(*del).expect("Deletion failed");
-    //             }
-    //         }
-        
-    //         let data = fs::read_to_string(interface_path).unwrap();
-    //         let mut de = ron::de::Deserializer::from_str(&data).expect("Couldn't create deserializer.");
-        
-    //         {
-    //             let mut d = (&mut world.entities(), &mut world.write_storage::<SimpleMarker<SerializerFlag>>(), &mut world.insert_resource::<SimpleMarkerAllocator<SerializerFlag>>());
-        
-    //             deserialize_individually!(
-    //                 world, de, d,
-    //                 InputComponent,
-    //                 CameraComponent,
-    //                 TransformComponent,
-    //                 TransformUiComponent,
-    //                 DebugUiComponent,
-    //                 RenderableComponent,
-    //                 DirectionalLightComponent,
-    //                 AmbientLightingComponent,
-    //                 TerrainComponent,
-    //                 TerrainUiComponent,
-    //                 GeometryComponent
-    //             );
-    //         }
+
+        let data = match self.does_save_exist(interface_path) {
+            true => match fs::read_to_string(interface_path) {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    log::error!("Couldn't read scene interface {}: {}", interface_path, e);
+                    None
+                }
+            },
+            false => {
+                log::info!("No scene interface at {}, starting with an empty scene.", interface_path);
+                None
+            }
+        };
+
+        let registry = self.component_registry.clone();
+        let mut scene = self.get_staged_scene().expect("Couldn't get staged scene during load.");
+        scene.insert_resource(SceneInterfacePath(interface_path));
+        scene.insert_resource(registry.clone());
+
+        let data = match data {
+            Some(data) => data,
+            None => return false,
+        };
+
+        log::info!("Loading scene interface from {}...", interface_path);
+        let mut world = scene.get_world().expect("Couldn't get world out of staged scene during load.");
+        match registry.deserialize_world(&mut world, &data) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("Couldn't deserialize scene interface {}: {}", interface_path, e);
+                false
+            }
         }
+    }
 
-    //     // finally, set that scene as active
-    //     // self.activate_staged_scene();
-    
-    //     // let mut deleteme : Option<Entity> = None;
-    //     // {
-    //     //     let entities = ecs.entities();
-    //     //     let helper = ecs.read_storage::<SerializationHelper>();
-    //     //     let player = ecs.read_storage::<Player>();
-    //     //     let position = ecs.read_storage::<Position>();
-    //     //     for (e,h) in (&entities, &helper).join() {
-    //     //         let mut worldmap = ecs.insert_resource::<super::map::Map>();
-    //     //         *worldmap = h.map.clone();
-    //     //         worldmap.tile_content = vec![Vec::new(); super::map::MAPCOUNT];
-    //     //         deleteme = Some(e);
-    //     //     }
-    //     //     for (e,_p,pos) in (&entities, &player, &position).join() {
-    //     //         let mut ppos = ecs.insert_resource::<rltk::Point>();
-    //     //         *ppos = rltk::Point::new(pos.x, pos.y);
-    //     //         let mut player_resource = ecs.insert_resource::<Entity>();
-    //     //         *player_resource = e;
-    //     //     }
-    //     // }
-    //     // ecs.delete_entity(deleteme.unwrap()).expect("Unable to delete helper");
-    // }
+    pub fn component_registry(&mut self) -> &mut ComponentRegistry {
+        &mut self.component_registry
+    }
 }
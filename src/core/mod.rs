@@ -1,6 +1,8 @@
 pub mod application;
+pub mod input;
 pub mod managers;
 pub mod rendering;
 pub mod plugins;
 pub mod scene;
-pub mod systems;
\ No newline at end of file
+pub mod systems;
+pub mod physics;
\ No newline at end of file
@@ -0,0 +1,80 @@
+use cgmath::Vector3;
+
+// pairs of boxes that overlap, by index. boxes are sorted along x and swept so only boxes that overlap
+// on x get checked against each other
+pub fn sweep_and_prune(boxes: &[(Vector3<f32>, Vector3<f32>)]) -> Vec<(usize, usize)> {
+    let mut order = (0..boxes.len()).collect::<Vec<usize>>();
+    order.sort_by(|a, b| boxes[*a].0.x.partial_cmp(&boxes[*b].0.x).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut pairs = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+    for i in order {
+        let (min, max) = boxes[i];
+        active.retain(|j| boxes[*j].1.x >= min.x);
+        for j in active.iter() {
+            let (other_min, other_max) = boxes[*j];
+            if min.y <= other_max.y && max.y >= other_min.y && min.z <= other_max.z && max.z >= other_min.z {
+                pairs.push(((*j).min(i), (*j).max(i)));
+            }
+        }
+        active.push(i);
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box(x: f32, y: f32, z: f32) -> (Vector3<f32>, Vector3<f32>) {
+        (Vector3::new(x, y, z), Vector3::new(x + 1.0, y + 1.0, z + 1.0))
+    }
+
+    #[test]
+    fn overlapping_boxes_pair_up_lowest_index_first() {
+        let boxes = [unit_box(0.5, 0.0, 0.0), unit_box(0.0, 0.0, 0.0)];
+        assert_eq!(sweep_and_prune(&boxes), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn touching_boxes_count_as_overlapping() {
+        let boxes = [unit_box(0.0, 0.0, 0.0), unit_box(1.0, 0.0, 0.0), unit_box(0.0, 0.0, 1.0)];
+        let mut pairs = sweep_and_prune(&boxes);
+        pairs.sort();
+        assert_eq!(pairs, vec![(0, 1), (0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn boxes_apart_on_any_axis_dont_pair() {
+        let boxes = [
+            unit_box(0.0, 0.0, 0.0),
+            // apart along x
+            unit_box(3.0, 0.0, 0.0),
+            // overlapping on x but apart along y
+            unit_box(0.5, 3.0, 0.0),
+            // overlapping on x but apart along z
+            unit_box(0.5, 0.0, 3.0),
+        ];
+        assert!(sweep_and_prune(&boxes).is_empty());
+    }
+
+    #[test]
+    fn box_inside_another_pairs() {
+        let boxes = [
+            (Vector3::new(0.0, 0.0, 0.0), Vector3::new(10.0, 10.0, 10.0)),
+            (Vector3::new(4.0, 4.0, 4.0), Vector3::new(5.0, 5.0, 5.0)),
+        ];
+        assert_eq!(sweep_and_prune(&boxes), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn long_box_pairs_with_boxes_past_shorter_ones() {
+        // the first box is still active when the last one is swept
+        let boxes = [
+            (Vector3::new(0.0, 0.0, 0.0), Vector3::new(10.0, 1.0, 1.0)),
+            unit_box(2.0, 5.0, 0.0),
+            unit_box(8.0, 0.0, 0.0),
+        ];
+        assert_eq!(sweep_and_prune(&boxes), vec![(0, 2)]);
+    }
+}
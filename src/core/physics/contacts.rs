@@ -0,0 +1,428 @@
+use cgmath::{
+    InnerSpace,
+    Matrix4,
+    Vector3,
+};
+
+use crate::core::physics::Heightfield;
+use crate::core::rendering::geometries::normal_matrix;
+use crate::core::physics::shapes::{
+    WorldShape,
+    box_corners,
+    box_radius,
+};
+
+// where two shapes overlap. the normal points from the first shape into the second, moving the second
+// along it by depth separates them
+#[derive(Debug, Clone, Copy)]
+pub struct Contact{
+    pub point: Vector3<f32>,
+    pub normal: Vector3<f32>,
+    pub depth: f32,
+}
+
+impl Contact{
+    fn flipped(self) -> Self {
+        Contact{
+            normal: -self.normal,
+            ..self
+        }
+    }
+}
+
+// narrow phase. None if the shapes don't touch. two heightfields never collide
+pub fn collide(a: &WorldShape, b: &WorldShape) -> Option<Contact> {
+    match (*a, *b) {
+        (WorldShape::Sphere{center: ca, radius: ra}, WorldShape::Sphere{center: cb, radius: rb}) => sphere_sphere(ca, ra, cb, rb),
+        (WorldShape::Sphere{center, radius}, WorldShape::Capsule{a: sa, b: sb, radius: rc}) => {
+            sphere_sphere(center, radius, closest_point_on_segment(center, sa, sb), rc)
+        },
+        (WorldShape::Capsule{a: a0, b: a1, radius: ra}, WorldShape::Capsule{a: b0, b: b1, radius: rb}) => {
+            let (p, q) = closest_points_on_segments(a0, a1, b0, b1);
+            sphere_sphere(p, ra, q, rb)
+        },
+        (WorldShape::Sphere{center, radius}, WorldShape::Box{center: box_center, axes, half_extents}) => {
+            sphere_box(center, radius, box_center, &axes, half_extents)
+        },
+        (WorldShape::Capsule{a: sa, b: sb, radius}, WorldShape::Box{center, axes, half_extents}) => {
+            // walk between the segment and the box a few times to find the segment point nearest the box
+            let mut point = (sa + sb) * 0.5;
+            for _ in 0..4 {
+                let on_box = closest_point_on_box(point, center, &axes, half_extents);
+                point = closest_point_on_segment(on_box, sa, sb);
+            }
+            sphere_box(point, radius, center, &axes, half_extents)
+        },
+        (WorldShape::Box{center: ca, axes: axes_a, half_extents: ha}, WorldShape::Box{center: cb, axes: axes_b, half_extents: hb}) => {
+            box_box(ca, &axes_a, ha, cb, &axes_b, hb)
+        },
+        (WorldShape::Heightfield{..}, WorldShape::Heightfield{..}) => None,
+        (shape, WorldShape::Heightfield{field, model_to_world, world_to_model}) => {
+            let points: Vec<(Vector3<f32>, f32)> = match shape {
+                WorldShape::Sphere{center, radius} => vec![(center, radius)],
+                WorldShape::Capsule{a, b, radius} => vec![(a, radius), (b, radius)],
+                WorldShape::Box{center, axes, half_extents} => box_corners(center, &axes, half_extents).iter().map(|corner| (*corner, 0.0)).collect(),
+                WorldShape::Heightfield{..} => Vec::new(),
+            };
+            points_heightfield(&points, field, &model_to_world, &world_to_model)
+        },
+        // everything else is one of the above the other way around
+        (first, second) => collide(&second, &first).map(Contact::flipped),
+    }
+}
+
+fn sphere_sphere(ca: Vector3<f32>, ra: f32, cb: Vector3<f32>, rb: f32) -> Option<Contact> {
+    let offset = cb - ca;
+    let distance = offset.magnitude();
+    if distance >= ra + rb {
+        return None;
+    }
+    // dead center, pick a direction
+    let normal = if distance > f32::EPSILON { offset / distance } else { Vector3::unit_z() };
+    let depth = ra + rb - distance;
+    Some(Contact{
+        point: ca + normal * (ra - depth * 0.5),
+        normal: normal,
+        depth: depth,
+    })
+}
+
+pub fn closest_point_on_segment(point: Vector3<f32>, a: Vector3<f32>, b: Vector3<f32>) -> Vector3<f32> {
+    let segment = b - a;
+    let length2 = segment.magnitude2();
+    if length2 < f32::EPSILON {
+        return a;
+    }
+    let t = ((point - a).dot(segment) / length2).clamp(0.0, 1.0);
+    a + segment * t
+}
+
+// closest pair of points between two segments
+pub fn closest_points_on_segments(a0: Vector3<f32>, a1: Vector3<f32>, b0: Vector3<f32>, b1: Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let r = a0 - b0;
+    let la = da.magnitude2();
+    let lb = db.magnitude2();
+    let f = db.dot(r);
+
+    if la < f32::EPSILON && lb < f32::EPSILON {
+        return (a0, b0);
+    }
+    let (s, t) = if la < f32::EPSILON {
+        (0.0, (f / lb).clamp(0.0, 1.0))
+    } else {
+        let c = da.dot(r);
+        if lb < f32::EPSILON {
+            ((-c / la).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = da.dot(db);
+            let denominator = la * lb - b * b;
+            // parallel segments, any s works
+            let mut s = if denominator > f32::EPSILON { ((b * f - c * lb) / denominator).clamp(0.0, 1.0) } else { 0.0 };
+            let mut t = (b * s + f) / lb;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / la).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / la).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+    (a0 + da * s, b0 + db * t)
+}
+
+pub fn closest_point_on_box(point: Vector3<f32>, center: Vector3<f32>, axes: &[Vector3<f32>; 3], half_extents: Vector3<f32>) -> Vector3<f32> {
+    let offset = point - center;
+    let mut closest = center;
+    for i in 0..3 {
+        let distance = offset.dot(axes[i]).clamp(-half_extents[i], half_extents[i]);
+        closest += axes[i] * distance;
+    }
+    closest
+}
+
+fn sphere_box(center: Vector3<f32>, radius: f32, box_center: Vector3<f32>, axes: &[Vector3<f32>; 3], half_extents: Vector3<f32>) -> Option<Contact> {
+    let closest = closest_point_on_box(center, box_center, axes, half_extents);
+    let offset = closest - center;
+    let distance = offset.magnitude();
+    if distance > f32::EPSILON {
+        if distance >= radius {
+            return None;
+        }
+        return Some(Contact{
+            point: closest,
+            normal: offset / distance,
+            depth: radius - distance,
+        });
+    }
+
+    // the center is inside the box, push out through the nearest face
+    let local = center - box_center;
+    let (axis, gap) = (0..3)
+        .map(|i| (i, half_extents[i] - local.dot(axes[i]).abs()))
+        .fold((0, f32::INFINITY), |nearest, face| if face.1 < nearest.1 { face } else { nearest });
+    let outward = if local.dot(axes[axis]) >= 0.0 { axes[axis] } else { -axes[axis] };
+    Some(Contact{
+        point: center + outward * gap,
+        normal: -outward,
+        depth: radius + gap,
+    })
+}
+
+// separating axis test over both boxes' faces and the edge pairs. the axis with the least overlap is the
+// contact normal
+fn box_box(
+    ca: Vector3<f32>, axes_a: &[Vector3<f32>; 3], ha: Vector3<f32>,
+    cb: Vector3<f32>, axes_b: &[Vector3<f32>; 3], hb: Vector3<f32>,
+) -> Option<Contact> {
+    let offset = cb - ca;
+    let mut candidates = Vec::with_capacity(15);
+    candidates.extend_from_slice(axes_a);
+    candidates.extend_from_slice(axes_b);
+    for a in axes_a.iter() {
+        for b in axes_b.iter() {
+            candidates.push(a.cross(*b));
+        }
+    }
+
+    let mut best: Option<(Vector3<f32>, f32)> = None;
+    for axis in candidates {
+        // edges that are parallel don't give an axis
+        if axis.magnitude2() < 1e-6 {
+            continue;
+        }
+        let axis = axis.normalize();
+        let distance = offset.dot(axis);
+        let overlap = box_radius(axes_a, ha, axis) + box_radius(axes_b, hb, axis) - distance.abs();
+        if overlap < 0.0 {
+            return None;
+        }
+        if best.map_or(true, |(_, best_overlap)| overlap < best_overlap) {
+            let normal = if distance >= 0.0 { axis } else { -axis };
+            best = Some((normal, overlap));
+        }
+    }
+
+    let (normal, depth) = best?;
+    // b's corner furthest into a
+    let point = box_corners(cb, axes_b, hb).iter()
+        .cloned()
+        .fold(cb, |deepest, corner| if corner.dot(normal) < deepest.dot(normal) { corner } else { deepest });
+    Some(Contact{
+        point: point + normal * (depth * 0.5),
+        normal: normal,
+        depth: depth,
+    })
+}
+
+// rounded points against the surface. the deepest one wins
+fn points_heightfield(points: &[(Vector3<f32>, f32)], field: &Heightfield, model_to_world: &Matrix4<f32>, world_to_model: &Matrix4<f32>) -> Option<Contact> {
+    let normal_to_world = normal_matrix(model_to_world);
+    let mut deepest: Option<Contact> = None;
+    for (point, radius) in points.iter() {
+        let local = (world_to_model * point.extend(1.0)).truncate();
+        let (height, local_normal) = match (field.height_at(local.x, local.y), field.normal_at(local.x, local.y)) {
+            (Some(height), Some(normal)) => (height, normal),
+            _ => continue,
+        };
+        let surface = (model_to_world * Vector3::new(local.x, local.y, height).extend(1.0)).truncate();
+        let normal = (normal_to_world * local_normal.extend(0.0)).truncate().normalize();
+        let depth = radius - (point - surface).dot(normal);
+        if depth <= 0.0 {
+            continue;
+        }
+        if deepest.map_or(true, |contact| depth > contact.depth) {
+            // the shape is the first of the pair, so it gets pushed out along the surface normal
+            deepest = Some(Contact{
+                point: point - normal * *radius,
+                normal: -normal,
+                depth: depth,
+            });
+        }
+    }
+    deepest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::SquareMatrix;
+
+    use crate::core::rendering::geometries::Vertex;
+
+    fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        (a - b).magnitude() < 1e-5
+    }
+
+    fn world_axes() -> [Vector3<f32>; 3] {
+        [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()]
+    }
+
+    fn sphere(x: f32, y: f32, z: f32, radius: f32) -> WorldShape<'static> {
+        WorldShape::Sphere{
+            center: Vector3::new(x, y, z),
+            radius: radius,
+        }
+    }
+
+    fn cube(x: f32, y: f32, z: f32, half_extent: f32) -> WorldShape<'static> {
+        WorldShape::Box{
+            center: Vector3::new(x, y, z),
+            axes: world_axes(),
+            half_extents: Vector3::new(half_extent, half_extent, half_extent),
+        }
+    }
+
+    #[test]
+    fn overlapping_spheres_push_apart_along_centers() {
+        let contact = collide(&sphere(0.0, 0.0, 0.0, 1.0), &sphere(1.5, 0.0, 0.0, 1.0)).unwrap();
+        assert!(close(contact.normal, Vector3::unit_x()));
+        assert!((contact.depth - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn touching_and_separated_spheres_dont_collide() {
+        assert!(collide(&sphere(0.0, 0.0, 0.0, 1.0), &sphere(2.0, 0.0, 0.0, 1.0)).is_none());
+        assert!(collide(&sphere(0.0, 0.0, 0.0, 1.0), &sphere(2.5, 0.0, 0.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn sphere_box_normal_points_into_the_box() {
+        let contact = collide(&sphere(1.8, 0.0, 0.0, 1.0), &cube(0.0, 0.0, 0.0, 1.0)).unwrap();
+        assert!(close(contact.normal, -Vector3::unit_x()));
+        assert!((contact.depth - 0.2).abs() < 1e-5);
+        assert!(close(contact.point, Vector3::new(1.0, 0.0, 0.0)));
+
+        // the other way around flips the normal
+        let flipped = collide(&cube(0.0, 0.0, 0.0, 1.0), &sphere(1.8, 0.0, 0.0, 1.0)).unwrap();
+        assert!(close(flipped.normal, Vector3::unit_x()));
+    }
+
+    #[test]
+    fn touching_and_separated_sphere_box_dont_collide() {
+        let axes = world_axes();
+        let half_extents = Vector3::new(1.0, 1.0, 1.0);
+        assert!(sphere_box(Vector3::new(2.0, 0.0, 0.0), 1.0, Vector3::new(0.0, 0.0, 0.0), &axes, half_extents).is_none());
+        assert!(sphere_box(Vector3::new(2.5, 0.0, 0.0), 1.0, Vector3::new(0.0, 0.0, 0.0), &axes, half_extents).is_none());
+    }
+
+    #[test]
+    fn sphere_inside_box_leaves_through_the_nearest_face() {
+        let axes = world_axes();
+        let contact = sphere_box(Vector3::new(0.5, 0.0, 0.0), 0.25, Vector3::new(0.0, 0.0, 0.0), &axes, Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        assert!(close(contact.normal, -Vector3::unit_x()));
+        assert!((contact.depth - 0.75).abs() < 1e-5);
+    }
+
+    #[test]
+    fn overlapping_boxes_separate_along_the_shallowest_axis() {
+        let axes = world_axes();
+        let half_extents = Vector3::new(1.0, 1.0, 1.0);
+        let contact = box_box(Vector3::new(0.0, 0.0, 0.0), &axes, half_extents, Vector3::new(0.0, 1.5, 0.2), &axes, half_extents).unwrap();
+        assert!(close(contact.normal, Vector3::unit_y()));
+        assert!((contact.depth - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn touching_boxes_have_no_depth_and_separated_boxes_dont_collide() {
+        let axes = world_axes();
+        let half_extents = Vector3::new(1.0, 1.0, 1.0);
+        let touching = box_box(Vector3::new(0.0, 0.0, 0.0), &axes, half_extents, Vector3::new(2.0, 0.0, 0.0), &axes, half_extents).unwrap();
+        assert!(touching.depth.abs() < 1e-5);
+        assert!(box_box(Vector3::new(0.0, 0.0, 0.0), &axes, half_extents, Vector3::new(2.5, 0.0, 0.0), &axes, half_extents).is_none());
+    }
+
+    #[test]
+    fn box_inside_box_is_pushed_out_the_nearest_side() {
+        let axes = world_axes();
+        let contact = box_box(
+            Vector3::new(0.0, 0.0, 0.0), &axes, Vector3::new(2.0, 2.0, 2.0),
+            Vector3::new(0.2, 0.0, 0.0), &axes, Vector3::new(0.5, 0.5, 0.5),
+        ).unwrap();
+        assert!(close(contact.normal, Vector3::unit_x()));
+        assert!((contact.depth - 2.3).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rotated_box_uses_its_own_axes() {
+        // b turned 45 degrees around z, so its corner reaches sqrt(2) along x
+        let s = std::f32::consts::FRAC_1_SQRT_2;
+        let rotated = [Vector3::new(s, s, 0.0), Vector3::new(-s, s, 0.0), Vector3::unit_z()];
+        let half_extents = Vector3::new(1.0, 1.0, 1.0);
+        let contact = box_box(Vector3::new(0.0, 0.0, 0.0), &world_axes(), half_extents, Vector3::new(2.2, 0.0, 0.0), &rotated, half_extents).unwrap();
+        assert!(close(contact.normal, Vector3::unit_x()));
+        assert!((contact.depth - (2.0_f32.sqrt() - 1.2)).abs() < 1e-5);
+        assert!(box_box(Vector3::new(0.0, 0.0, 0.0), &world_axes(), half_extents, Vector3::new(2.5, 0.0, 0.0), &rotated, half_extents).is_none());
+    }
+
+    #[test]
+    fn closest_points_on_crossing_segments() {
+        let (p, q) = closest_points_on_segments(
+            Vector3::new(-1.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, -1.0, 1.0), Vector3::new(0.0, 1.0, 1.0),
+        );
+        assert!(close(p, Vector3::new(0.0, 0.0, 0.0)));
+        assert!(close(q, Vector3::new(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn closest_points_clamp_to_the_segment_ends() {
+        let (p, q) = closest_points_on_segments(
+            Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(2.0, -1.0, 0.0), Vector3::new(2.0, 1.0, 0.0),
+        );
+        assert!(close(p, Vector3::new(1.0, 0.0, 0.0)));
+        assert!(close(q, Vector3::new(2.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn closest_points_on_parallel_and_degenerate_segments() {
+        let (p, q) = closest_points_on_segments(
+            Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0), Vector3::new(1.0, 1.0, 0.0),
+        );
+        assert!(((p - q).magnitude() - 1.0).abs() < 1e-5);
+
+        let point = Vector3::new(0.5, 2.0, 0.0);
+        let (p, q) = closest_points_on_segments(
+            point, point,
+            Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0),
+        );
+        assert!(close(p, point));
+        assert!(close(q, Vector3::new(0.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn capsules_collide_between_their_closest_points() {
+        let a = WorldShape::Capsule{a: Vector3::new(0.0, 0.0, -1.0), b: Vector3::new(0.0, 0.0, 1.0), radius: 0.5};
+        let b = WorldShape::Capsule{a: Vector3::new(0.8, -1.0, 0.0), b: Vector3::new(0.8, 1.0, 0.0), radius: 0.5};
+        let contact = collide(&a, &b).unwrap();
+        assert!(close(contact.normal, Vector3::unit_x()));
+        assert!((contact.depth - 0.2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn sphere_resting_on_flat_heightfield() {
+        let size = 3;
+        let vertices = (0..size)
+            .flat_map(|x| (0..size).map(move |y| Vertex::new(x as f32, y as f32, 0.0)))
+            .collect::<Vec<Vertex>>();
+        let field = Heightfield::from_vertices(size, &vertices, 1);
+        let ground = WorldShape::Heightfield{
+            field: &field,
+            model_to_world: Matrix4::identity(),
+            world_to_model: Matrix4::identity(),
+        };
+
+        let contact = collide(&sphere(1.0, 1.0, 0.5, 1.0), &ground).unwrap();
+        // from the sphere down into the ground
+        assert!(close(contact.normal, -Vector3::unit_z()));
+        assert!((contact.depth - 0.5).abs() < 1e-5);
+        assert!(collide(&sphere(1.0, 1.0, 1.5, 1.0), &ground).is_none());
+        // off the edge of the grid
+        assert!(collide(&sphere(5.0, 1.0, 0.5, 1.0), &ground).is_none());
+    }
+}
@@ -0,0 +1,177 @@
+use cgmath::{
+    InnerSpace,
+    Vector3,
+};
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::core::rendering::geometries::BoundingVolume;
+use crate::core::rendering::geometries::Vertex;
+
+// a grid of heights one unit apart, heights[x][y] is the z at (x, y) in model space. this is the layout
+// TerrainGeometry lays its vertices out in
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Heightfield{
+    // rebuilt from the terrain, so it isn't written into scene files
+    #[serde(skip)]
+    pub heights: Vec<Vec<f32>>,
+    // which generation of the terrain the heights were copied from
+    #[serde(skip)]
+    pub generation: u64,
+}
+
+impl Heightfield{
+    // a size by size grid of vertices stored column by column, vertices[x * size + y] is at (x, y)
+    pub fn from_vertices(size: usize, vertices: &[Vertex], generation: u64) -> Self {
+        if vertices.len() != size * size {
+            log::error!("Can't build a {0}x{0} heightfield from {1} vertices.", size, vertices.len());
+            return Heightfield{
+                heights: Vec::new(),
+                generation: generation,
+            };
+        }
+        Heightfield{
+            heights: vertices.chunks(size.max(1))
+                .map(|column| column.iter().map(|vertex| vertex.position[2]).collect())
+                .collect(),
+            generation: generation,
+        }
+    }
+
+    // samples along x and y
+    pub fn size(&self) -> (usize, usize) {
+        (self.heights.len(), self.heights.first().map_or(0, |column| column.len()))
+    }
+
+    pub fn height_at(&self, x: f32, y: f32) -> Option<f32> {
+        bilinear_height(self.size(), |x, y| self.heights[x][y], x, y)
+    }
+
+    // model space
+    pub fn normal_at(&self, x: f32, y: f32) -> Option<Vector3<f32>> {
+        bilinear_normal(self.size(), |x, y| self.heights[x][y], x, y)
+    }
+
+    pub fn bounds(&self) -> BoundingVolume {
+        BoundingVolume::from_points(self.heights.iter().enumerate().flat_map(|(x, column)| {
+            column.iter().enumerate().map(move |(y, height)| Vector3::new(x as f32, y as f32, *height))
+        }))
+    }
+}
+
+// the cell a point is over and how far across it the point is. None off the edge of the grid
+fn cell(size: (usize, usize), x: f32, y: f32) -> Option<(usize, usize, f32, f32)> {
+    let (size_x, size_y) = size;
+    if size_x < 2 || size_y < 2 {
+        return None;
+    }
+    let max_x = (size_x - 1) as f32;
+    let max_y = (size_y - 1) as f32;
+    if !(0.0..=max_x).contains(&x) || !(0.0..=max_y).contains(&y) {
+        return None;
+    }
+    let cell_x = (x.floor() as usize).min(size_x - 2);
+    let cell_y = (y.floor() as usize).min(size_y - 2);
+    Some((cell_x, cell_y, x - cell_x as f32, y - cell_y as f32))
+}
+
+// height of a grid of samples one unit apart, blended between the four corners of the cell the point is over
+pub fn bilinear_height<F>(size: (usize, usize), height: F, x: f32, y: f32) -> Option<f32>
+where
+    F: Fn(usize, usize) -> f32,
+{
+    let (cell_x, cell_y, fx, fy) = cell(size, x, y)?;
+    let h00 = height(cell_x, cell_y);
+    let h10 = height(cell_x + 1, cell_y);
+    let h01 = height(cell_x, cell_y + 1);
+    let h11 = height(cell_x + 1, cell_y + 1);
+    let bottom = h00 + (h10 - h00) * fx;
+    let top = h01 + (h11 - h01) * fx;
+    Some(bottom + (top - bottom) * fy)
+}
+
+// normal of the same bilinear surface
+pub fn bilinear_normal<F>(size: (usize, usize), height: F, x: f32, y: f32) -> Option<Vector3<f32>>
+where
+    F: Fn(usize, usize) -> f32,
+{
+    let (cell_x, cell_y, fx, fy) = cell(size, x, y)?;
+    let h00 = height(cell_x, cell_y);
+    let h10 = height(cell_x + 1, cell_y);
+    let h01 = height(cell_x, cell_y + 1);
+    let h11 = height(cell_x + 1, cell_y + 1);
+    let dx = (h10 - h00) + ((h11 - h01) - (h10 - h00)) * fy;
+    let dy = (h01 - h00) + ((h11 - h10) - (h01 - h00)) * fx;
+    Some(Vector3::new(-dx, -dy, 1.0).normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // h = x * y is exactly bilinear, so every sample has a known answer
+    const SIZE: (usize, usize) = (3, 3);
+
+    fn saddle(x: usize, y: usize) -> f32 {
+        (x * y) as f32
+    }
+
+    fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        (a - b).magnitude() < 1e-5
+    }
+
+    #[test]
+    fn height_blends_inside_a_cell() {
+        assert_eq!(bilinear_height(SIZE, saddle, 1.5, 0.5), Some(0.75));
+        assert_eq!(bilinear_height(SIZE, saddle, 0.25, 1.75), Some(0.4375));
+    }
+
+    #[test]
+    fn height_is_exact_on_cell_edges_and_corners() {
+        assert_eq!(bilinear_height(SIZE, saddle, 1.0, 0.5), Some(0.5));
+        assert_eq!(bilinear_height(SIZE, saddle, 0.5, 1.0), Some(0.5));
+        assert_eq!(bilinear_height(SIZE, saddle, 1.0, 1.0), Some(1.0));
+        // the far edges use the last cell rather than one past it
+        assert_eq!(bilinear_height(SIZE, saddle, 2.0, 1.5), Some(3.0));
+        assert_eq!(bilinear_height(SIZE, saddle, 2.0, 2.0), Some(4.0));
+        assert_eq!(bilinear_height(SIZE, saddle, 0.0, 0.0), Some(0.0));
+    }
+
+    #[test]
+    fn normal_follows_the_slope() {
+        // the surface z = x * y has normal (-y, -x, 1)
+        for &(x, y) in &[(0.5, 0.5), (1.0, 0.5), (1.5, 1.0), (2.0, 2.0), (0.0, 2.0)] {
+            let normal = bilinear_normal(SIZE, saddle, x, y).unwrap();
+            assert!(close(normal, Vector3::new(-y, -x, 1.0).normalize()), "{:?} at ({}, {})", normal, x, y);
+        }
+    }
+
+    #[test]
+    fn off_the_grid_is_none() {
+        for &(x, y) in &[(-0.01, 1.0), (2.01, 1.0), (1.0, -0.01), (1.0, 2.01), (f32::NAN, 1.0)] {
+            assert!(bilinear_height(SIZE, saddle, x, y).is_none());
+            assert!(bilinear_normal(SIZE, saddle, x, y).is_none());
+        }
+    }
+
+    #[test]
+    fn grids_without_a_cell_are_none() {
+        assert!(bilinear_height((1, 3), saddle, 0.0, 0.0).is_none());
+        assert!(bilinear_normal((3, 1), saddle, 0.0, 0.0).is_none());
+        assert!(Heightfield::default().height_at(0.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn heightfield_reads_vertices_column_by_column() {
+        let vertices: Vec<Vertex> = (0..3)
+            .flat_map(|x| (0..3).map(move |y| Vertex::new(x as f32, y as f32, saddle(x, y))))
+            .collect();
+        let field = Heightfield::from_vertices(3, &vertices, 7);
+        assert_eq!(field.size(), (3, 3));
+        assert_eq!(field.generation, 7);
+        assert_eq!(field.height_at(2.0, 1.5), Some(3.0));
+        assert!(Heightfield::from_vertices(4, &vertices, 0).heights.is_empty());
+    }
+}
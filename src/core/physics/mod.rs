@@ -0,0 +1,10 @@
+pub mod heightfield;
+pub mod shapes;
+pub mod contacts;
+pub mod broad_phase;
+
+pub use heightfield::Heightfield;
+pub use shapes::WorldShape;
+pub use contacts::Contact;
+pub use contacts::collide;
+pub use broad_phase::sweep_and_prune;
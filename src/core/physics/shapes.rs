@@ -0,0 +1,130 @@
+use cgmath::{
+    InnerSpace,
+    Matrix4,
+    Rotation,
+    SquareMatrix,
+    Vector3,
+};
+
+use crate::core::plugins::components::{
+    Collider,
+    TransformComponent,
+};
+use crate::core::physics::Heightfield;
+
+// a collider placed in the world by its entity's transform. scale stretches boxes per axis, spheres and
+// capsules grow by the largest axis so they stay round
+#[derive(Debug, Clone, Copy)]
+pub enum WorldShape<'a>{
+    Sphere{
+        center: Vector3<f32>,
+        radius: f32,
+    },
+    Capsule{
+        a: Vector3<f32>,
+        b: Vector3<f32>,
+        radius: f32,
+    },
+    // aabbs are boxes whose axes are the world axes
+    Box{
+        center: Vector3<f32>,
+        axes: [Vector3<f32>; 3],
+        half_extents: Vector3<f32>,
+    },
+    Heightfield{
+        field: &'a Heightfield,
+        model_to_world: Matrix4<f32>,
+        world_to_model: Matrix4<f32>,
+    },
+}
+
+impl<'a> WorldShape<'a>{
+    // None when the transform squashes the collider flat
+    pub fn pose(collider: &'a Collider, transform: &TransformComponent) -> Option<Self> {
+        let scale = Vector3::new(transform.scale.x.abs(), transform.scale.y.abs(), transform.scale.z.abs());
+        let largest = scale.x.max(scale.y).max(scale.z);
+        let center = transform.translation;
+        match collider {
+            Collider::Sphere{radius} => Some(WorldShape::Sphere{
+                center: center,
+                radius: radius * largest,
+            }),
+            Collider::Capsule{radius, half_height} => {
+                let offset = transform.rotation.rotate_vector(Vector3::new(0.0, 0.0, half_height * scale.z));
+                Some(WorldShape::Capsule{
+                    a: center - offset,
+                    b: center + offset,
+                    radius: radius * scale.x.max(scale.y),
+                })
+            },
+            Collider::Aabb{half_extents} => Some(WorldShape::Box{
+                center: center,
+                axes: [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()],
+                half_extents: multiply(*half_extents, scale),
+            }),
+            Collider::Obb{half_extents} => Some(WorldShape::Box{
+                center: center,
+                axes: [transform.right(), transform.forward(), transform.up()],
+                half_extents: multiply(*half_extents, scale),
+            }),
+            Collider::Heightfield(field) => {
+                let model_to_world = transform.matrix();
+                let world_to_model = model_to_world.invert()?;
+                Some(WorldShape::Heightfield{
+                    field: field,
+                    model_to_world: model_to_world,
+                    world_to_model: world_to_model,
+                })
+            },
+        }
+    }
+
+    // world space box around the shape, for the broad phase
+    pub fn aabb(&self) -> (Vector3<f32>, Vector3<f32>) {
+        match *self {
+            WorldShape::Sphere{center, radius} => {
+                let extent = Vector3::new(radius, radius, radius);
+                (center - extent, center + extent)
+            },
+            WorldShape::Capsule{a, b, radius} => {
+                let extent = Vector3::new(radius, radius, radius);
+                (
+                    Vector3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)) - extent,
+                    Vector3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)) + extent,
+                )
+            },
+            WorldShape::Box{center, axes, half_extents} => {
+                let extent = Vector3::new(
+                    box_radius(&axes, half_extents, Vector3::unit_x()),
+                    box_radius(&axes, half_extents, Vector3::unit_y()),
+                    box_radius(&axes, half_extents, Vector3::unit_z()),
+                );
+                (center - extent, center + extent)
+            },
+            WorldShape::Heightfield{field, model_to_world, ..} => {
+                let bounds = field.bounds().transformed(&model_to_world);
+                (bounds.min, bounds.max)
+            },
+        }
+    }
+}
+
+fn multiply(a: Vector3<f32>, b: Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(a.x * b.x, a.y * b.y, a.z * b.z)
+}
+
+// how far a box reaches along an axis from its center
+pub fn box_radius(axes: &[Vector3<f32>; 3], half_extents: Vector3<f32>, axis: Vector3<f32>) -> f32 {
+    (0..3).map(|i| half_extents[i] * axes[i].dot(axis).abs()).sum()
+}
+
+pub fn box_corners(center: Vector3<f32>, axes: &[Vector3<f32>; 3], half_extents: Vector3<f32>) -> [Vector3<f32>; 8] {
+    let mut corners = [center; 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        for axis in 0..3 {
+            let sign = if i & (1 << axis) == 0 { -1.0 } else { 1.0 };
+            *corner += axes[axis] * (half_extents[axis] * sign);
+        }
+    }
+    corners
+}
@@ -14,9 +14,68 @@ use serde::{
 };
 
 
+// how the camera flattens the world. orthographic size is the height of the view in world units, the width
+// follows from the aspect ratio
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Projection{
+    Perspective{fov: f32},
+    Orthographic{size: f32},
+}
+
+impl Default for Projection{
+    fn default() -> Self {
+        Projection::Perspective{fov: 3.1415 / 1.75}
+    }
+}
+
+// part of the screen a camera draws to, as fractions of the full width and height from the top left
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ViewportRect{
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewportRect{
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        ViewportRect{
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+
+    pub fn full() -> Self {
+        ViewportRect::new(0.0, 0.0, 1.0, 1.0)
+    }
+}
+
+// halves clip z and moves it up by half of w
+fn vulkan_depth() -> Matrix4<f32> {
+    Matrix4::new(
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 0.5, 0.0,
+        0.0, 0.0, 0.5, 1.0,
+    )
+}
+
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct CameraComponent{
-    pub fov: f32,
+    // inactive cameras don't draw. every active camera gets its own slice of the depth range, and the higher the
+    // priority the nearer the slice, so higher priorities draw on top. the highest one is the main camera
+    #[serde(default = "CameraComponent::default_active")]
+    pub active: bool,
+    #[serde(default)]
+    pub priority: i32,
+    // None draws to the whole screen
+    #[serde(default)]
+    pub viewport: Option<ViewportRect>,
+    // defaulted so scenes saved before projections existed still load
+    #[serde(default)]
+    pub projection: Projection,
     pub near: f32,
     pub far: f32,
     pub aspect: f32,
@@ -30,7 +89,6 @@ pub struct CameraComponent{
 
 impl CameraComponent {
     pub fn create_default() -> Self {
-        let fov = 3.1415 / 1.75;
         let near = 0.001;
         let far = 1e6;
         let aspect = 0.5;
@@ -38,7 +96,10 @@ impl CameraComponent {
         let eye = Vector3::new(3.0, 0.0, -3.0);
         let up = Vector3::new(0.0, 0.0, 1.0);
         let mut cam = CameraComponent{
-            fov: fov,
+            active: true,
+            priority: 0,
+            viewport: None,
+            projection: Projection::default(),
             near: near,
             far: far,
             aspect: aspect,
@@ -54,8 +115,54 @@ impl CameraComponent {
         cam
     }
 
+    fn default_active() -> bool {
+        true
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_viewport(mut self, viewport: ViewportRect) -> Self {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    pub fn create_orthographic(size: f32) -> Self {
+        let mut cam = CameraComponent::create_default();
+        cam.set_projection(Projection::Orthographic{size: size});
+        cam
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+        self.calculate_perspective();
+    }
+
+    // builds the projection matrix, perspective or orthographic. the perspective field holds it either way.
+    // cgmath builds opengl matrices with depth from -1 to 1, vulkan clips anything below 0, so both get squeezed
+    // into 0 to 1 with the near plane at 0
     pub fn calculate_perspective(&mut self) {
-        self.perspective = cgmath::perspective(Rad(self.fov), self.aspect, self.near, self.far);
+        self.perspective = vulkan_depth() * match self.projection {
+            Projection::Perspective{fov} => cgmath::perspective(Rad(fov), self.aspect, self.near, self.far),
+            Projection::Orthographic{size} => {
+                let half_height = size / 2.0;
+                let half_width = half_height * self.aspect;
+                cgmath::ortho(-half_width, half_width, -half_height, half_height, self.near, self.far)
+            },
+        };
+    }
+
+    pub fn is_orthographic(&self) -> bool {
+        matches!(self.projection, Projection::Orthographic{..})
+    }
+
+    // moving an orthographic camera closer doesn't make anything bigger, so zooming scales the view instead
+    pub fn zoom_orthographic(&mut self, factor: f32) {
+        if let Projection::Orthographic{size} = self.projection {
+            self.set_projection(Projection::Orthographic{size: (size * factor).max(0.01)});
+        }
     }
 
     pub fn calculate_view(&mut self) {
@@ -66,6 +173,18 @@ impl CameraComponent {
         ) * self.correction_matrix;
     }
 
+    // for cameras attached to something. eye, look_at and up are relative to it
+    pub fn calculate_view_attached(&mut self, model_to_world: &Matrix4<f32>) {
+        let eye = (model_to_world * self.eye.extend(1.0)).truncate();
+        let look_at = (model_to_world * self.look_at.extend(1.0)).truncate();
+        let up = (model_to_world * self.up.extend(0.0)).truncate();
+        self.view = Matrix4::look_at_rh(
+            Point3::new(eye.x, eye.y, eye.z),
+            Point3::new(look_at.x, look_at.y, look_at.z),
+            up
+        ) * self.correction_matrix;
+    }
+
     pub fn get_view(&self) -> Matrix4<f32> {
         self.view.clone()
     }
@@ -74,3 +193,35 @@ impl CameraComponent {
         self.perspective.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::Vector4;
+
+    // clip space of a point straight ahead of the camera, d units away
+    fn clip_ahead(camera: &CameraComponent, d: f32) -> Vector4<f32> {
+        camera.perspective * Vector4::new(0.0, 0.0, -d, 1.0)
+    }
+
+    fn assert_depth_range(camera: &CameraComponent) {
+        for d in [camera.near * 1.5, 1.0, 10.0, 1000.0, camera.far * 0.9].iter() {
+            let clip = clip_ahead(camera, *d);
+            assert!(clip.z >= 0.0 && clip.z <= clip.w, "depth {} landed at z {} w {}", d, clip.z, clip.w);
+        }
+        let near = clip_ahead(camera, camera.near);
+        assert!((near.z / near.w).abs() < 1e-4);
+        let far = clip_ahead(camera, camera.far);
+        assert!((far.z / far.w - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn perspective_depth_is_between_zero_and_w() {
+        assert_depth_range(&CameraComponent::create_default());
+    }
+
+    #[test]
+    fn orthographic_depth_is_between_zero_and_w() {
+        assert_depth_range(&CameraComponent::create_orthographic(20.0));
+    }
+}
@@ -0,0 +1,106 @@
+use bevy_ecs::component::Component;
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+// controllers sit next to a CameraComponent and move its eye and look_at from input. they assume z is up
+// like the default camera. speeds are per second, sensitivities are radians or world units per unit of
+// raw mouse motion.
+
+// free flying, fps style. move axes fly along where the camera is looking, holding the right mouse button
+// (or grabbing the cursor) looks around
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct FlyCameraController{
+    pub move_speed: f32,
+    pub look_sensitivity: f32,
+}
+
+impl FlyCameraController{
+    pub fn new(move_speed: f32, look_sensitivity: f32) -> Self {
+        FlyCameraController{
+            move_speed: move_speed,
+            look_sensitivity: look_sensitivity,
+        }
+    }
+}
+
+impl Default for FlyCameraController{
+    fn default() -> Self {
+        FlyCameraController{
+            move_speed: 5.0,
+            look_sensitivity: 0.003,
+        }
+    }
+}
+
+// circles look_at. left mouse drag or the orbit axis rotates, the wheel zooms in and out
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct OrbitCameraController{
+    pub rotate_sensitivity: f32,
+    // radians per second for the orbit axis
+    pub rotate_speed: f32,
+    // fraction of the distance each wheel line zooms
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+impl OrbitCameraController{
+    pub fn new(rotate_sensitivity: f32, zoom_speed: f32) -> Self {
+        OrbitCameraController{
+            rotate_sensitivity: rotate_sensitivity,
+            zoom_speed: zoom_speed,
+            ..OrbitCameraController::default()
+        }
+    }
+}
+
+impl Default for OrbitCameraController{
+    fn default() -> Self {
+        OrbitCameraController{
+            rotate_sensitivity: 0.005,
+            rotate_speed: 1.5,
+            zoom_speed: 0.1,
+            min_distance: 0.5,
+            max_distance: 500.0,
+        }
+    }
+}
+
+// editor style. move axes and middle mouse drag pan across the ground, the wheel zooms towards look_at and
+// the orbit axis turns around it
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct PanZoomCameraController{
+    pub pan_speed: f32,
+    // scaled by distance so dragging feels the same zoomed in or out
+    pub drag_sensitivity: f32,
+    pub rotate_speed: f32,
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+impl PanZoomCameraController{
+    pub fn new(pan_speed: f32, zoom_speed: f32) -> Self {
+        PanZoomCameraController{
+            pan_speed: pan_speed,
+            zoom_speed: zoom_speed,
+            ..PanZoomCameraController::default()
+        }
+    }
+}
+
+impl Default for PanZoomCameraController{
+    fn default() -> Self {
+        PanZoomCameraController{
+            pan_speed: 5.0,
+            drag_sensitivity: 0.002,
+            rotate_speed: 1.5,
+            zoom_speed: 0.1,
+            min_distance: 0.5,
+            max_distance: 500.0,
+        }
+    }
+}
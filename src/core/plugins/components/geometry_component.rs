@@ -1,94 +1,64 @@
 
-use std::sync::Arc;
+use std::fs;
 use serde::{Serialize, Deserialize};
-use vulkano::{
-    buffer::CpuAccessibleBuffer,
-    device::Device,
-    buffer::BufferUsage,
-};
 use bevy_ecs::component::Component;
 
-use crate::core::rendering::geometries::Vertex;
+use crate::core::rendering::MeshHandle;
 
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GeometryType{
     Triangle,
     Box,
     Plane,
+    // generated shapes, all centered on the origin with z up. segments go around z
+    SubdividedPlane{size: f32, subdivisions: u32},
+    Sphere{radius: f32, segments: u32, rings: u32},
+    Cylinder{radius: f32, height: f32, segments: u32},
+    Cone{radius: f32, height: f32, segments: u32},
+    // sides go around the tube
+    Torus{radius: f32, tube_radius: f32, segments: u32, sides: u32},
+    // height is the straight part, rings are per end
+    Capsule{radius: f32, height: f32, segments: u32, rings: u32},
+    // obj, gltf or glb file, loaded when the scene is set up
+    Mesh(String),
 }
 
+impl GeometryType{
+    // geometries with the same key get the same mesh. files are keyed by their full path so different
+    // ways of writing the same path still share
+    pub fn asset_key(&self) -> String {
+        match self {
+            GeometryType::Mesh(path) => {
+                let path = fs::canonicalize(path)
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .unwrap_or_else(|_| path.clone());
+                format!("Mesh({:?})", path)
+            },
+            _ => format!("{:?}", self),
+        }
+    }
+}
+
+// which mesh an entity draws. the mesh itself lives in the scene's MeshAssets, shared with every other
+// entity of the same geometry type
 #[derive(Component, Clone, Serialize, Deserialize)]
 pub struct GeometryComponent{
-    pub vertices: Vec<Vertex>,
-    pub indices: Vec<u16>,
-    #[serde(skip, default="GeometryComponent::default_vertex_buffer")]
-    pub vertex_buffer: Option<Arc<CpuAccessibleBuffer<[Vertex]>>>,
-    #[serde(skip, default="GeometryComponent::default_index_buffer")]
-    pub index_buffer: Option<Arc<CpuAccessibleBuffer<[u16]>>>,
-    pub initialized: bool,
     pub geometry_type: GeometryType,
+    // handed out when the scene is set up
+    #[serde(skip)]
+    pub mesh: Option<MeshHandle>,
 }
 
 impl GeometryComponent{
     pub fn create(t: GeometryType) -> Self {
         GeometryComponent{
-            vertices: Vec::new(),
-            indices: Vec::new(),
-            vertex_buffer: None,
-            index_buffer: None,
-            initialized: false,
             geometry_type: t,
+            mesh: None,
         }
     }
-    pub fn default_vertex_buffer() -> Option<Arc<CpuAccessibleBuffer<[Vertex]>>> {
-        None
-    }
-    pub fn default_index_buffer() -> Option<Arc<CpuAccessibleBuffer<[u16]>>> {
-        None
-    }
-
-    // ---- 
-    pub fn initialize(&mut self, device: Arc<Device>){
-        // Vertex buffer init
-        let vertex_buffer = {
-            CpuAccessibleBuffer::from_iter(
-                device.clone(),
-                BufferUsage::all(),
-                false,
-                self.vertices.clone()
-                .iter()
-                .cloned(),
-            )
-            .unwrap()
-        };
-
-        // index buffer init
-        let index_buffer = CpuAccessibleBuffer::from_iter(
-            device.clone(),
-            BufferUsage::all(),
-            false,
-            self.indices.clone()
-            .iter()
-            .cloned(),
-        ).unwrap();
-
-        log::debug!("Setting vbuffer right here.");
-
-        self.vertex_buffer = Some(vertex_buffer);
-        self.index_buffer = Some(index_buffer);
-        self.initialized = true;
-    }
-
-    pub fn vertex_buffer(&self) -> Arc<CpuAccessibleBuffer<[Vertex]>> {
-        self.vertex_buffer.clone().unwrap().clone()
-    }
-
-    pub fn index_buffer(&self) -> Arc<CpuAccessibleBuffer<[u16]>> {
-        self.index_buffer.clone().unwrap().clone()
-    }
 
     pub fn is_initialized(&self) -> bool {
-        self.initialized
-    }  
-}
\ No newline at end of file
+        self.mesh.is_some()
+    }
+}
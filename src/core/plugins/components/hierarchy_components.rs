@@ -0,0 +1,121 @@
+use bevy_ecs::{
+    component::Component,
+    entity::{
+        Entity,
+        EntityMap,
+        MapEntities,
+        MapEntitiesError,
+    },
+};
+
+use cgmath::{
+    Matrix4,
+    SquareMatrix,
+    Vector3,
+};
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::core::rendering::geometries::normal_matrix;
+
+// entities are written out by id. loading remaps them onto the newly spawned entities
+mod entity_id {
+    use bevy_ecs::entity::Entity;
+    use serde::{
+        Serialize,
+        Serializer,
+        Deserialize,
+        Deserializer,
+    };
+
+    pub fn serialize<S: Serializer>(entity: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
+        entity.id().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
+        u32::deserialize(deserializer).map(Entity::from_raw)
+    }
+}
+
+mod entity_ids {
+    use bevy_ecs::entity::Entity;
+    use serde::{
+        Serialize,
+        Serializer,
+        Deserialize,
+        Deserializer,
+    };
+
+    pub fn serialize<S: Serializer>(entities: &Vec<Entity>, serializer: S) -> Result<S::Ok, S::Error> {
+        entities.iter().map(|entity| entity.id()).collect::<Vec<u32>>().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Entity>, D::Error> {
+        Vec::<u32>::deserialize(deserializer).map(|ids| ids.into_iter().map(Entity::from_raw).collect())
+    }
+}
+
+// the entity this one is attached to. its transform is relative to the parent's.
+// SetParent keeps the parent's Children in sync straight away, a Parent inserted directly is caught up on
+// by ParentSyncSystem before the next propagation
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Parent(#[serde(with = "entity_id")] pub Entity);
+
+impl MapEntities for Parent{
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        self.0 = entity_map.get(self.0)?;
+        Ok(())
+    }
+}
+
+#[derive(Component, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Children(#[serde(with = "entity_ids")] pub Vec<Entity>);
+
+impl MapEntities for Children{
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        for entity in self.0.iter_mut() {
+            *entity = entity_map.get(*entity)?;
+        }
+        Ok(())
+    }
+}
+
+// model to world for this frame, parents included and blended between ticks the same way the draw
+// systems blend. written by the transform propagation system, runtime only.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct GlobalTransform{
+    pub matrix: Matrix4<f32>,
+}
+
+impl GlobalTransform{
+    pub fn new(matrix: Matrix4<f32>) -> Self {
+        GlobalTransform{
+            matrix: matrix,
+        }
+    }
+
+    pub fn translation(&self) -> Vector3<f32> {
+        self.matrix.w.truncate()
+    }
+
+    // a point relative to this entity, in world space
+    pub fn transform_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        (self.matrix * point.extend(1.0)).truncate()
+    }
+
+    pub fn transform_vector(&self, vector: Vector3<f32>) -> Vector3<f32> {
+        (self.matrix * vector.extend(0.0)).truncate()
+    }
+
+    pub fn normal_matrix(&self) -> Matrix4<f32> {
+        normal_matrix(&self.matrix)
+    }
+}
+
+impl Default for GlobalTransform{
+    fn default() -> Self{
+        GlobalTransform::new(Matrix4::identity())
+    }
+}
@@ -49,4 +49,32 @@ impl Default for AmbientLightingComponent {
             color: [1.0, 1.0, 1.0],
         }
     }
-}
\ No newline at end of file
+}
+
+// lights everything within range of its position, fading out towards the edge
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct PointLightComponent{
+    pub position: Vector3<f32>,
+    pub color: [f32; 3],
+    pub range: f32,
+}
+
+impl PointLightComponent{
+    pub fn new(position: Vector3<f32>, color: [f32; 3], range: f32) -> Self {
+        PointLightComponent{
+            position: position,
+            color: color,
+            range: range,
+        }
+    }
+}
+
+impl Default for PointLightComponent {
+    fn default() -> Self {
+        PointLightComponent{
+            position: Vector3::new(0.0, 0.0, 0.0),
+            color: [1.0, 1.0, 1.0],
+            range: 10.0,
+        }
+    }
+}
@@ -1,7 +1,9 @@
 pub mod transform_component;
+pub mod hierarchy_components;
 pub mod velocity_component;
 pub mod renderable_component;
 pub mod camera_component;
+pub mod camera_controller_components;
 pub mod input_component;
 pub mod debug_ui_component;
 pub mod egui_component;
@@ -9,20 +11,36 @@ pub mod light_components;
 pub mod terrain_component;
 pub mod serializer_component;
 pub mod geometry_component;
+pub mod physics_components;
 pub mod ui;
 
 pub use input_component::InputComponent;
 pub use camera_component::CameraComponent;
+pub use camera_component::Projection;
+pub use camera_component::ViewportRect;
+pub use camera_controller_components::FlyCameraController;
+pub use camera_controller_components::OrbitCameraController;
+pub use camera_controller_components::PanZoomCameraController;
 pub use transform_component::TransformComponent;
 pub use transform_component::TransformUiComponent;
+pub use transform_component::PreviousTransformComponent;
 pub use transform_component::TransformBuilder;
+pub use hierarchy_components::Parent;
+pub use hierarchy_components::Children;
+pub use hierarchy_components::GlobalTransform;
+pub use velocity_component::VelocityComponent;
+pub use physics_components::BodyType;
+pub use physics_components::RigidBody;
+pub use physics_components::Collider;
 pub use debug_ui_component::DebugUiComponent;
 pub use egui_component::EguiComponent;
 pub use renderable_component::RenderableComponent;
 pub use light_components::DirectionalLightComponent;
 pub use light_components::AmbientLightingComponent;
+pub use light_components::PointLightComponent;
 pub use terrain_component::TerrainComponent;
 pub use terrain_component::TerrainUiComponent;
+pub use terrain_component::TerrainSample;
 pub use serializer_component::SerializerFlag;
 pub use geometry_component::GeometryComponent;
 pub use geometry_component::GeometryType;
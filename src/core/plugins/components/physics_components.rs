@@ -0,0 +1,141 @@
+use bevy_ecs::component::Component;
+
+use cgmath::Vector3;
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::core::physics::Heightfield;
+use crate::core::rendering::geometries::BoundingVolume;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BodyType{
+    // moved by gravity and collisions
+    Dynamic,
+    // never moves
+    Static,
+    // moved only by its VelocityComponent, pushes dynamic bodies out of the way
+    Kinematic,
+}
+
+// makes a collider take part in collision response. colliders without one act like static bodies.
+// dynamic bodies get a VelocityComponent if they don't have one
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct RigidBody{
+    pub body_type: BodyType,
+    pub mass: f32,
+    // 0 doesn't bounce at all, 1 keeps all its speed
+    pub restitution: f32,
+    pub friction: f32,
+    pub gravity_scale: f32,
+}
+
+impl RigidBody{
+    pub fn dynamic(mass: f32) -> Self {
+        RigidBody{
+            body_type: BodyType::Dynamic,
+            mass: mass,
+            restitution: 0.2,
+            friction: 0.5,
+            gravity_scale: 1.0,
+        }
+    }
+
+    pub fn fixed() -> Self {
+        RigidBody{
+            body_type: BodyType::Static,
+            ..RigidBody::dynamic(0.0)
+        }
+    }
+
+    pub fn kinematic() -> Self {
+        RigidBody{
+            body_type: BodyType::Kinematic,
+            ..RigidBody::dynamic(0.0)
+        }
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_gravity_scale(mut self, gravity_scale: f32) -> Self {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        self.body_type == BodyType::Dynamic
+    }
+
+    // 0 for anything collisions can't move
+    pub fn inverse_mass(&self) -> f32 {
+        if self.is_dynamic() && self.mass > 0.0 {
+            1.0 / self.mass
+        } else {
+            0.0
+        }
+    }
+}
+
+// the shape an entity collides with, in model space around its transform's translation
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub enum Collider{
+    Sphere{
+        radius: f32,
+    },
+    // stays lined up with the world axes however the entity is rotated
+    Aabb{
+        half_extents: Vector3<f32>,
+    },
+    // turns with the entity
+    Obb{
+        half_extents: Vector3<f32>,
+    },
+    // along the entity's up axis. half_height is from the center to the middle of each cap
+    Capsule{
+        radius: f32,
+        half_height: f32,
+    },
+    // kept in sync with the TerrainComponent on the same entity
+    Heightfield(Heightfield),
+}
+
+impl Collider{
+    pub fn sphere(radius: f32) -> Self {
+        Collider::Sphere{radius: radius}
+    }
+
+    pub fn aabb(half_extents: Vector3<f32>) -> Self {
+        Collider::Aabb{half_extents: half_extents}
+    }
+
+    pub fn obb(half_extents: Vector3<f32>) -> Self {
+        Collider::Obb{half_extents: half_extents}
+    }
+
+    pub fn capsule(radius: f32, half_height: f32) -> Self {
+        Collider::Capsule{radius: radius, half_height: half_height}
+    }
+
+    pub fn heightfield() -> Self {
+        Collider::Heightfield(Heightfield::default())
+    }
+
+    // box around a piece of geometry. geometry that isn't centered on its origin gets a box that is
+    pub fn from_bounds(bounds: &BoundingVolume) -> Self {
+        let half_extents = Vector3::new(
+            bounds.min.x.abs().max(bounds.max.x.abs()),
+            bounds.min.y.abs().max(bounds.max.y.abs()),
+            bounds.min.z.abs().max(bounds.max.z.abs()),
+        );
+        Collider::obb(half_extents)
+    }
+}
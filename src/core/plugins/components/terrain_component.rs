@@ -1,7 +1,17 @@
 use bevy_ecs::component::Component;
 
 use crate::core::rendering::geometries::TerrainGeometry;
-use vulkano::device::Device;
+use crate::core::rendering::geometries::normal_matrix;
+use crate::core::plugins::components::TransformComponent;
+use cgmath::{
+    Angle,
+    InnerSpace,
+    Rad,
+    SquareMatrix,
+    Vector2,
+    Vector3,
+};
+use vulkano::device::Queue;
 use std::sync::{Arc, Mutex};
 use serde::{
     Serialize,
@@ -24,10 +34,10 @@ impl TerrainComponent{
         }
     }
 
-    pub fn initialize(&mut self, device: Arc<Device>){
+    pub fn initialize(&mut self, queue: Arc<Queue>){
         log::debug!("Initializing renderable component...");
         let geometry = self.geometry.clone();//.as_ref();//unwrap();
-        geometry.lock().unwrap().initialize(device);
+        geometry.lock().unwrap().initialize(queue);
     }
 
     pub fn initialized(&self) -> bool {
@@ -41,4 +51,140 @@ impl TerrainComponent{
     pub fn get_size(&self) -> usize {
         self.geometry.clone().lock().unwrap().size
     }
-}
\ No newline at end of file
+
+    // the ground under a world space (x, y), with the terrain placed by its transform. None when the point
+    // isn't over the terrain
+    pub fn sample(&self, transform: &TransformComponent, x: f32, y: f32) -> Option<TerrainSample> {
+        let model_to_world = transform.matrix();
+        let world_to_model = model_to_world.invert()?;
+        let geometry = self.geometry.lock().unwrap();
+
+        // a tilted terrain's model space vertical isn't the world's, so nudge the sample point until the
+        // surface point lands under (x, y). one pass is exact when the terrain is only turned around z
+        let target = Vector2::new(x, y);
+        let mut local = (world_to_model * Vector3::new(x, y, 0.0).extend(1.0)).truncate();
+        let mut surface = local;
+        for _ in 0..4 {
+            surface = Vector3::new(local.x, local.y, geometry.height_at(local.x, local.y)?);
+            let world = (model_to_world * surface.extend(1.0)).truncate();
+            let error = target - Vector2::new(world.x, world.y);
+            if error.magnitude2() < 1e-8 {
+                break;
+            }
+            local += (world_to_model * error.extend(0.0).extend(0.0)).truncate();
+        }
+
+        let local_normal = geometry.normal_at(surface.x, surface.y)?;
+        let point = (model_to_world * surface.extend(1.0)).truncate();
+        let normal = (normal_matrix(&model_to_world) * local_normal.extend(0.0)).truncate().normalize();
+        Some(TerrainSample{
+            point: point,
+            normal: normal,
+            slope: Rad::acos(normal.z.clamp(-1.0, 1.0)),
+        })
+    }
+
+    pub fn height_at(&self, transform: &TransformComponent, x: f32, y: f32) -> Option<f32> {
+        self.sample(transform, x, y).map(|sample| sample.point.z)
+    }
+
+    pub fn normal_at(&self, transform: &TransformComponent, x: f32, y: f32) -> Option<Vector3<f32>> {
+        self.sample(transform, x, y).map(|sample| sample.normal)
+    }
+
+    pub fn slope_at(&self, transform: &TransformComponent, x: f32, y: f32) -> Option<Rad<f32>> {
+        self.sample(transform, x, y).map(|sample| sample.slope)
+    }
+
+    // puts something on the ground where it already is, lifted by offset. false if it isn't over the terrain
+    pub fn snap_to_ground(&self, terrain_transform: &TransformComponent, transform: &mut TransformComponent, offset: f32) -> bool {
+        match self.height_at(terrain_transform, transform.translation.x, transform.translation.y) {
+            Some(height) => {
+                transform.translation.z = height + offset;
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+// a point on the terrain surface in world space
+#[derive(Debug, Clone, Copy)]
+pub struct TerrainSample{
+    pub point: Vector3<f32>,
+    pub normal: Vector3<f32>,
+    // angle between the surface and flat ground
+    pub slope: Rad<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{
+        Deg,
+        Quaternion,
+        Rotation3,
+    };
+
+    // a 5x5 plane rising along x and twice as fast along y, z = x + 2y in model space
+    fn ramp() -> TerrainComponent {
+        let terrain = TerrainComponent::create(5);
+        terrain.geometry.lock().unwrap().height_map = (0..5)
+            .map(|x| (0..5).map(|y| (x + 2 * y) as f64).collect())
+            .collect();
+        terrain
+    }
+
+    fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        (a - b).magnitude() < 1e-4
+    }
+
+    #[test]
+    fn untransformed_terrain_samples_its_height_map() {
+        let terrain = ramp();
+        let transform = TransformComponent::create_empty();
+        let sample = terrain.sample(&transform, 1.5, 0.5).unwrap();
+        assert!(close(sample.point, Vector3::new(1.5, 0.5, 2.5)));
+        assert!(close(sample.normal, Vector3::new(-1.0, -2.0, 1.0).normalize()));
+        // the far corner is still on the terrain, just past it isn't
+        assert!(close(terrain.sample(&transform, 4.0, 4.0).unwrap().point, Vector3::new(4.0, 4.0, 12.0)));
+        assert!(terrain.sample(&transform, 4.01, 2.0).is_none());
+        assert!(terrain.sample(&transform, 2.0, -0.01).is_none());
+    }
+
+    #[test]
+    fn rotated_terrain_turns_its_slope() {
+        let terrain = ramp();
+        // a quarter turn around z, then moved along x so the terrain covers x in 0..4 again
+        let transform = TransformComponent::create(
+            Vector3::new(4.0, 0.0, 0.0),
+            Quaternion::from_angle_z(Deg(90.0)),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        // world (3, 1) is model (1, 1)
+        let sample = terrain.sample(&transform, 3.0, 1.0).unwrap();
+        assert!(close(sample.point, Vector3::new(3.0, 1.0, 3.0)));
+        // the model space normal (-1, -2, 1) turned a quarter around z
+        assert!(close(sample.normal, Vector3::new(2.0, -1.0, 1.0).normalize()));
+        assert!((sample.slope.0 - Rad::acos(sample.normal.z).0).abs() < 1e-5);
+        // world (5, 1) is model (1, -1), off the grid
+        assert!(terrain.sample(&transform, 5.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn scaled_terrain_keeps_normals_perpendicular() {
+        let terrain = ramp();
+        let transform = TransformComponent::create(
+            Vector3::new(0.0, 0.0, 0.0),
+            Quaternion::from_angle_z(Deg(0.0)),
+            Vector3::new(2.0, 1.0, 3.0),
+        );
+        // world (2, 1) is model (1, 1), three times as high
+        let sample = terrain.sample(&transform, 2.0, 1.0).unwrap();
+        assert!(close(sample.point, Vector3::new(2.0, 1.0, 9.0)));
+        // in world space the surface is z = 1.5x + 6y
+        assert!(close(sample.normal, Vector3::new(-1.5, -6.0, 1.0).normalize()));
+        assert!(terrain.height_at(&transform, 8.0, 4.0).is_some());
+        assert!(terrain.height_at(&transform, 8.1, 4.0).is_none());
+    }
+}
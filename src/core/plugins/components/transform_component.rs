@@ -1,11 +1,17 @@
 use bevy_ecs::component::Component;
 
 use cgmath::{
+    Euler,
+    InnerSpace,
+    Matrix3,
     Matrix4,
+    One,
+    Quaternion,
+    Rad,
+    Rotation,
     Vector3,
+    VectorSpace,
 };
-use std::sync::Arc;
-use std::sync::Mutex;
 use serde::{
     Serialize,
     Deserialize,
@@ -14,105 +20,185 @@ use serde::{
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct TransformUiComponent;
 
-#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+// where an entity is, which way it faces and how big it is. z is up, and an unrotated transform faces
+// down +y with +x to its right
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct TransformComponent{
-    pub global_position: Arc<Mutex<Vector3<f32>>>,
-    pub rotation: Arc<Mutex<Matrix4<f32>>>,
-    pub scale: Arc<Mutex<f32>>,
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
 }
 
 impl TransformComponent{
     pub fn create_empty() -> Self {
         TransformComponent{
-            global_position: Arc::new(Mutex::new(Vector3::new(0.0, 0.0, 0.0))),
-            rotation: Arc::new(Mutex::new(Matrix4::from_scale(1.0))),
-            scale: Arc::new(Mutex::new(1.0)),
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn create(translation: Vector3<f32>, rotation: Quaternion<f32>, scale: Vector3<f32>) -> Self {
+        TransformComponent{
+            translation: translation,
+            rotation: rotation,
+            scale: scale,
         }
     }
 
-    pub fn create(global_pos: Vector3<f32>, rot: Matrix4<f32>, s: f32) -> Self {
+    pub fn from_translation(translation: Vector3<f32>) -> Self {
         TransformComponent{
-            global_position: Arc::new(Mutex::new(global_pos)),
-            rotation: Arc::new(Mutex::new(rot)),
-            scale: Arc::new(Mutex::new(s)),
+            translation: translation,
+            ..TransformComponent::create_empty()
+        }
+    }
+
+    // splits a model matrix back up. shear can't be represented so it gets dropped
+    pub fn from_matrix(matrix: &Matrix4<f32>) -> Self {
+        let x = matrix.x.truncate();
+        let y = matrix.y.truncate();
+        let z = matrix.z.truncate();
+        let scale = Vector3::new(x.magnitude(), y.magnitude(), z.magnitude());
+        if scale.x < f32::EPSILON || scale.y < f32::EPSILON || scale.z < f32::EPSILON {
+            return TransformComponent{
+                translation: matrix.w.truncate(),
+                rotation: Quaternion::one(),
+                scale: scale,
+            };
+        }
+        TransformComponent{
+            translation: matrix.w.truncate(),
+            rotation: Quaternion::from(Matrix3::from_cols(x / scale.x, y / scale.y, z / scale.z)).normalize(),
+            scale: scale,
         }
     }
 
     pub fn start() -> TransformBuilder{
-        TransformBuilder{
-            global_position: None,
-            rotation: None,
-            scale: None
+        TransformBuilder::new()
+    }
+
+    // model to world. scales, then rotates, then translates
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+
+    pub fn forward(&self) -> Vector3<f32> {
+        self.rotation.rotate_vector(Vector3::unit_y())
+    }
+
+    pub fn right(&self) -> Vector3<f32> {
+        self.rotation.rotate_vector(Vector3::unit_x())
+    }
+
+    pub fn up(&self) -> Vector3<f32> {
+        self.rotation.rotate_vector(Vector3::unit_z())
+    }
+
+    // turns to face a point. does nothing if the point is on top of us or straight along up
+    pub fn look_at(&mut self, target: Vector3<f32>, up: Vector3<f32>){
+        let forward = target - self.translation;
+        if forward.magnitude2() < f32::EPSILON {
+            return;
+        }
+        let forward = forward.normalize();
+        let right = forward.cross(up);
+        if right.magnitude2() < f32::EPSILON {
+            return;
         }
+        let right = right.normalize();
+        let up = right.cross(forward);
+        self.rotation = Quaternion::from(Matrix3::from_cols(right, forward, up)).normalize();
+    }
+
+    pub fn euler_angles(&self) -> Euler<Rad<f32>> {
+        Euler::from(self.rotation)
     }
 
-    pub fn global_position(&self) -> Vector3<f32> {
-        self.global_position.lock().expect("Transform can't read its own position.").clone()
+    pub fn set_euler_angles(&mut self, angles: Euler<Rad<f32>>){
+        self.rotation = Quaternion::from(angles);
     }
 
-    pub fn rotation(&self) -> Matrix4<f32> {
-        self.rotation.lock().expect("Transform can't read its own rotation.").clone()
+    pub fn translate(&mut self, offset: Vector3<f32>){
+        self.translation += offset;
     }
 
-    pub fn scale(&self) -> f32 {
-        self.scale.lock().expect("Transform can't read its own scale").clone()
+    // applied on top of the current rotation, in world space
+    pub fn rotate(&mut self, rotation: Quaternion<f32>){
+        self.rotation = (rotation * self.rotation).normalize();
+    }
+
+    pub fn snapshot(&self) -> PreviousTransformComponent {
+        PreviousTransformComponent(*self)
+    }
+
+    // the transform blended between the last tick's snapshot and now. alpha of 1 is the current transform.
+    // without a snapshot there's nothing to blend from so the current one is used
+    pub fn interpolated(&self, previous: Option<&PreviousTransformComponent>, alpha: f32) -> TransformComponent {
+        match previous {
+            Some(PreviousTransformComponent(previous)) => TransformComponent{
+                translation: previous.translation.lerp(self.translation, alpha),
+                rotation: previous.rotation.slerp(self.rotation, alpha),
+                scale: previous.scale.lerp(self.scale, alpha),
+            },
+            None => *self,
+        }
     }
 }
 
+// the transform as it was at the start of the last tick. render systems interpolate from this to the
+// current transform. runtime only, it gets recreated on load.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PreviousTransformComponent(pub TransformComponent);
+
 impl Default for TransformComponent{
     fn default() -> Self{
-        TransformComponent{
-            global_position: Arc::new(Mutex::new(Vector3::new(0.0, 0.0, 0.0))),
-            rotation: Arc::new(Mutex::new(Matrix4::from_scale(1.0))),
-            scale: Arc::new(Mutex::new(1.0))
-        }
+        TransformComponent::create_empty()
     }
 }
 
 pub struct TransformBuilder{
-    global_position: Option<Vector3<f32>>,
-    rotation: Option<Matrix4<f32>>,
-    scale: Option<f32>
+    translation: Option<Vector3<f32>>,
+    rotation: Option<Quaternion<f32>>,
+    scale: Option<Vector3<f32>>,
 }
 
 impl TransformBuilder{
     pub fn new() -> Self{
         TransformBuilder{
-            global_position: None,
+            translation: None,
             rotation: None,
             scale: None
         }
     }
 
     pub fn build(self) -> TransformComponent{
+        let empty = TransformComponent::create_empty();
         TransformComponent{
-            global_position: match self.global_position {
-                Some(gp) => Arc::new(Mutex::new(gp)),
-                None => Arc::new(Mutex::new(Vector3::new(0.0, 0.0, 0.0))),
-            },
-            rotation: match self.rotation {
-                Some(r) => Arc::new(Mutex::new(r)),
-                None => Arc::new(Mutex::new(Matrix4::from_scale(1.0))),
-            },
-            scale: match self.scale {
-                Some(s) => Arc::new(Mutex::new(s)),
-                None => Arc::new(Mutex::new(1.0)),
-            }
+            translation: self.translation.unwrap_or(empty.translation),
+            rotation: self.rotation.unwrap_or(empty.rotation),
+            scale: self.scale.unwrap_or(empty.scale),
         }
     }
 
-    pub fn with_global_position(mut self, pos: Vector3<f32>) -> Self {
-        self.global_position = Some(pos);
+    pub fn with_translation(mut self, translation: Vector3<f32>) -> Self {
+        self.translation = Some(translation);
         self
     }
 
-    pub fn with_rotation(mut self, rot: Matrix4<f32>) -> Self {
-        self.rotation = Some(rot);
+    pub fn with_rotation(mut self, rotation: Quaternion<f32>) -> Self {
+        self.rotation = Some(rotation);
         self
     }
 
-    pub fn with_scale(mut self, scale: f32) -> Self {
+    pub fn with_scale(mut self, scale: Vector3<f32>) -> Self {
         self.scale = Some(scale);
         self
     }
-}
\ No newline at end of file
+
+    pub fn with_uniform_scale(mut self, scale: f32) -> Self {
+        self.scale = Some(Vector3::new(scale, scale, scale));
+        self
+    }
+}
@@ -6,7 +6,63 @@ use serde::{
     Deserialize,
 };
 
+// moves an entity's transform every tick. units are per second, angular is an axis scaled by radians per
+// second. damping bleeds off that fraction of both velocities per second
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct VelocityComponent{
-    pub velocity: Vector3<f32>,
+    #[serde(alias = "velocity")]
+    pub linear: Vector3<f32>,
+    #[serde(default = "VelocityComponent::zero")]
+    pub angular: Vector3<f32>,
+    #[serde(default = "VelocityComponent::zero")]
+    pub acceleration: Vector3<f32>,
+    #[serde(default)]
+    pub damping: f32,
+    #[serde(default)]
+    pub max_speed: Option<f32>,
+}
+
+impl VelocityComponent{
+    pub fn new(linear: Vector3<f32>) -> Self {
+        VelocityComponent{
+            linear: linear,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_angular(mut self, angular: Vector3<f32>) -> Self {
+        self.angular = angular;
+        self
+    }
+
+    pub fn with_acceleration(mut self, acceleration: Vector3<f32>) -> Self {
+        self.acceleration = acceleration;
+        self
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = Some(max_speed);
+        self
+    }
+
+    fn zero() -> Vector3<f32> {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+}
+
+impl Default for VelocityComponent{
+    fn default() -> Self{
+        VelocityComponent{
+            linear: VelocityComponent::zero(),
+            angular: VelocityComponent::zero(),
+            acceleration: VelocityComponent::zero(),
+            damping: 0.0,
+            max_speed: None,
+        }
+    }
 }
@@ -0,0 +1,38 @@
+use bevy_ecs::prelude::Schedule;
+
+use crate::core::plugins::{
+    Plugin,
+    stages,
+};
+use crate::core::plugins::components::{
+    DirectionalLightComponent,
+    AmbientLightingComponent,
+    PointLightComponent,
+};
+use crate::core::scene::ComponentRegistry;
+use crate::core::systems::render_systems::{
+    DirectionalLightingSystem,
+    AmbientLightingSystem,
+    PointLightingSystem,
+};
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin{
+    fn name(&self) -> &'static str {
+        "lighting"
+    }
+
+    fn register_components(&self, registry: &mut ComponentRegistry){
+        registry.register::<DirectionalLightComponent>();
+        registry.register::<AmbientLightingComponent>();
+        registry.register::<PointLightComponent>();
+    }
+
+    fn build_render_schedule(&self, schedule: &mut Schedule){
+        schedule
+            .add_system_to_stage(stages::MAIN, DirectionalLightingSystem)
+            .add_system_to_stage(stages::MAIN, AmbientLightingSystem)
+            .add_system_to_stage(stages::MAIN, PointLightingSystem);
+    }
+}
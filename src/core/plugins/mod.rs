@@ -1 +1,13 @@
 pub mod components;
+pub mod plugin;
+pub mod terrain_plugin;
+pub mod lighting_plugin;
+pub mod ui_plugin;
+pub mod physics_plugin;
+
+pub use plugin::Plugin;
+pub use plugin::stages;
+pub use terrain_plugin::TerrainPlugin;
+pub use lighting_plugin::LightingPlugin;
+pub use ui_plugin::UiPlugin;
+pub use physics_plugin::PhysicsPlugin;
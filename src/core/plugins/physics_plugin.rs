@@ -0,0 +1,45 @@
+use bevy_ecs::prelude::Schedule;
+
+use crate::core::plugins::{
+    Plugin,
+    stages,
+};
+use crate::core::plugins::components::{
+    RigidBody,
+    Collider,
+};
+use crate::core::scene::{
+    ComponentRegistry,
+    Scene,
+    Staged,
+};
+use crate::core::systems::physics_systems::{
+    PhysicsSettings,
+    CollisionEvents,
+    PhysicsSystem,
+    TerrainColliderSystem,
+};
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin{
+    fn name(&self) -> &'static str {
+        "physics"
+    }
+
+    fn register_components(&self, registry: &mut ComponentRegistry){
+        registry.register::<RigidBody>();
+        registry.register::<Collider>();
+    }
+
+    fn insert_resources(&self, scene: &mut Scene<Staged>){
+        scene.insert_resource(PhysicsSettings::default());
+        scene.insert_resource(CollisionEvents::default());
+    }
+
+    fn build_update_schedule(&self, schedule: &mut Schedule){
+        schedule
+            .add_system_to_stage(stages::UPDATE, TerrainColliderSystem)
+            .add_system_to_stage(stages::PHYSICS, PhysicsSystem);
+    }
+}
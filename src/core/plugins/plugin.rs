@@ -0,0 +1,55 @@
+use bevy_ecs::prelude::Schedule;
+
+use crate::core::scene::{
+    Scene,
+    Staged,
+    ComponentRegistry,
+};
+
+// names of the stages plugins can add systems to. these are created by the scene before plugins are applied
+pub mod stages {
+    // setup schedule, runs once when a staged scene is activated
+    pub const GEOMETRY_INIT: &str = "geometry_init";
+    pub const FINAL_INIT: &str = "final_init";
+
+    // update schedule, runs every fixed tick. pre_update snapshots transforms for interpolation, physics
+    // runs once everything in update has moved
+    pub const PRE_UPDATE: &str = "pre_update";
+    pub const UPDATE: &str = "update";
+    pub const PHYSICS: &str = "physics";
+
+    // render schedule, runs every frame. propagate works out global transforms for cameras and draws, and hands
+    // geometries added since the last frame their meshes
+    pub const CAMERA_MOVE: &str = "camera_move";
+    pub const PROPAGATE: &str = "propagate";
+    pub const CAMERA_UPDATE: &str = "camera_update";
+    pub const INPUT: &str = "wireframe_input_system";
+    pub const ASSEMBLY_STATE: &str = "assembly_state_modifier_system";
+    pub const MAIN: &str = "main";
+    pub const UI: &str = "ui_stage";
+    // last in the render schedule. lets go of whatever was despawned this frame
+    pub const CLEANUP: &str = "cleanup";
+}
+
+// a bundle of components, systems and resources that gets applied to every scene the application stages.
+// every hook is optional.
+pub trait Plugin{
+    fn name(&self) -> &'static str;
+
+    // components that should be written out when a scene is saved
+    fn register_components(&self, _registry: &mut ComponentRegistry){
+    }
+
+    // called when a scene is staged, before any of the managers prep it
+    fn insert_resources(&self, _scene: &mut Scene<Staged>){
+    }
+
+    fn build_setup_schedule(&self, _schedule: &mut Schedule){
+    }
+
+    fn build_update_schedule(&self, _schedule: &mut Schedule){
+    }
+
+    fn build_render_schedule(&self, _schedule: &mut Schedule){
+    }
+}
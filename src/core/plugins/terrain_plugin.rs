@@ -0,0 +1,41 @@
+use bevy_ecs::prelude::Schedule;
+
+use crate::core::plugins::{
+    Plugin,
+    stages,
+};
+use crate::core::plugins::components::{
+    TerrainComponent,
+    TerrainUiComponent,
+};
+use crate::core::scene::ComponentRegistry;
+use crate::core::systems::{
+    TerrainInitSystem,
+    TerrainDrawSystem,
+    TerrainAssemblyStateModifierSystem,
+    TerrainUiSystem,
+};
+
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin{
+    fn name(&self) -> &'static str {
+        "terrain"
+    }
+
+    fn register_components(&self, registry: &mut ComponentRegistry){
+        registry.register::<TerrainComponent>();
+        registry.register::<TerrainUiComponent>();
+    }
+
+    fn build_setup_schedule(&self, schedule: &mut Schedule){
+        schedule.add_system_to_stage(stages::GEOMETRY_INIT, TerrainInitSystem);
+    }
+
+    fn build_render_schedule(&self, schedule: &mut Schedule){
+        schedule
+            .add_system_to_stage(stages::ASSEMBLY_STATE, TerrainAssemblyStateModifierSystem)
+            .add_system_to_stage(stages::MAIN, TerrainDrawSystem)
+            .add_system_to_stage(stages::UI, TerrainUiSystem);
+    }
+}
@@ -0,0 +1,34 @@
+use bevy_ecs::prelude::Schedule;
+
+use crate::core::plugins::{
+    Plugin,
+    stages,
+};
+use crate::core::plugins::components::{
+    DebugUiComponent,
+    AppInterfaceFlag,
+};
+use crate::core::scene::ComponentRegistry;
+use crate::core::systems::ui_systems::{
+    DebugUiSystem,
+    CameraUiSystem,
+};
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin{
+    fn name(&self) -> &'static str {
+        "ui"
+    }
+
+    fn register_components(&self, registry: &mut ComponentRegistry){
+        registry.register::<DebugUiComponent>();
+        registry.register::<AppInterfaceFlag>();
+    }
+
+    fn build_render_schedule(&self, schedule: &mut Schedule){
+        schedule
+            .add_system_to_stage(stages::UI, DebugUiSystem)
+            .add_system_to_stage(stages::UI, CameraUiSystem);
+    }
+}
@@ -0,0 +1,150 @@
+use cgmath::{
+    InnerSpace,
+    Matrix4,
+    Vector3,
+    Vector4,
+};
+use serde::{Serialize, Deserialize};
+
+use crate::core::rendering::geometries::Vertex;
+
+// axis aligned box and sphere around a piece of geometry, in whatever space its vertices are in. computed
+// when geometry is generated and moved into world space with the model matrix when it's needed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingVolume{
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingVolume{
+    pub fn from_points<I>(points: I) -> Self
+    where
+        I: IntoIterator<Item = Vector3<f32>>,
+    {
+        let points = points.into_iter().collect::<Vec<Vector3<f32>>>();
+        if points.is_empty() {
+            return BoundingVolume::default();
+        }
+
+        let mut min = points[0];
+        let mut max = points[0];
+        for point in points.iter() {
+            min = Vector3::new(min.x.min(point.x), min.y.min(point.y), min.z.min(point.z));
+            max = Vector3::new(max.x.max(point.x), max.y.max(point.y), max.z.max(point.z));
+        }
+
+        // sphere around the box center. not the tightest sphere, but it always contains every point
+        let center = (min + max) * 0.5;
+        let radius = points.iter()
+            .map(|point| (point - center).magnitude())
+            .fold(0.0, f32::max);
+
+        BoundingVolume{
+            min: min,
+            max: max,
+            center: center,
+            radius: radius,
+        }
+    }
+
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        BoundingVolume::from_points(vertices.iter().map(|vertex| Vector3::from(vertex.position)))
+    }
+
+    pub fn corners(&self) -> [Vector3<f32>; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Vector3::new(min.x, min.y, min.z),
+            Vector3::new(max.x, min.y, min.z),
+            Vector3::new(min.x, max.y, min.z),
+            Vector3::new(max.x, max.y, min.z),
+            Vector3::new(min.x, min.y, max.z),
+            Vector3::new(max.x, min.y, max.z),
+            Vector3::new(min.x, max.y, max.z),
+            Vector3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    // the box around the transformed corners and the sphere moved and grown by the largest scale
+    pub fn transformed(&self, model: &Matrix4<f32>) -> Self {
+        let corners = self.corners();
+        let mut bounds = BoundingVolume::from_points(corners.iter().map(|corner| (model * corner.extend(1.0)).truncate()));
+
+        let scale = model.x.truncate().magnitude()
+            .max(model.y.truncate().magnitude())
+            .max(model.z.truncate().magnitude());
+        bounds.center = (model * self.center.extend(1.0)).truncate();
+        bounds.radius = self.radius * scale;
+        bounds
+    }
+
+    pub fn contains_point(&self, point: Vector3<f32>) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x
+            && point.y >= self.min.y && point.y <= self.max.y
+            && point.z >= self.min.z && point.z <= self.max.z
+    }
+}
+
+impl Default for BoundingVolume{
+    fn default() -> Self {
+        BoundingVolume{
+            min: Vector3::new(0.0, 0.0, 0.0),
+            max: Vector3::new(0.0, 0.0, 0.0),
+            center: Vector3::new(0.0, 0.0, 0.0),
+            radius: 0.0,
+        }
+    }
+}
+
+// the six planes of a camera's view volume, pointing inwards. xyz is the normal, w the distance
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum{
+    pub planes: [Vector4<f32>; 6],
+}
+
+impl Frustum{
+    // pulls the planes straight out of a projection * view matrix
+    pub fn from_view_projection(matrix: &Matrix4<f32>) -> Self {
+        let row = |i: usize| Vector4::new(matrix.x[i], matrix.y[i], matrix.z[i], matrix.w[i]);
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+        let normalize = |plane: Vector4<f32>| plane / plane.truncate().magnitude();
+        Frustum{
+            planes: [
+                normalize(r3 + r0), // left
+                normalize(r3 - r0), // right
+                normalize(r3 + r1), // bottom
+                normalize(r3 - r1), // top
+                // camera projections put the near plane at 0 like vulkan does
+                normalize(r2), // near
+                normalize(r3 - r2), // far
+            ],
+        }
+    }
+
+    fn distance(plane: &Vector4<f32>, point: Vector3<f32>) -> f32 {
+        plane.truncate().dot(point) + plane.w
+    }
+
+    pub fn intersects_sphere(&self, center: Vector3<f32>, radius: f32) -> bool {
+        self.planes.iter().all(|plane| Frustum::distance(plane, center) >= -radius)
+    }
+
+    // tests the corner of the box furthest along each plane's normal
+    pub fn intersects_aabb(&self, min: Vector3<f32>, max: Vector3<f32>) -> bool {
+        self.planes.iter().all(|plane| {
+            let furthest = Vector3::new(
+                if plane.x >= 0.0 { max.x } else { min.x },
+                if plane.y >= 0.0 { max.y } else { min.y },
+                if plane.z >= 0.0 { max.z } else { min.z },
+            );
+            Frustum::distance(plane, furthest) >= 0.0
+        })
+    }
+
+    // sphere first since it's cheaper, then the box for anything the sphere lets through
+    pub fn intersects(&self, bounds: &BoundingVolume) -> bool {
+        self.intersects_sphere(bounds.center, bounds.radius) && self.intersects_aabb(bounds.min, bounds.max)
+    }
+}
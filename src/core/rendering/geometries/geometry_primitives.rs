@@ -1,17 +1,94 @@
 use bytemuck::{Pod, Zeroable};
 use serde::{Serialize, Deserialize};
 
+// what the g-buffer pass draws. tangent.w is the handedness of the bitangent, cross(normal, tangent) * w
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod, Serialize, Deserialize)]
 pub struct Vertex {
     pub position: [f32; 3],
+    #[serde(default="Vertex::default_normal")]
+    pub normal: [f32; 3],
+    #[serde(default)]
+    pub uv: [f32; 2],
+    #[serde(default="Vertex::default_tangent")]
+    pub tangent: [f32; 4],
+    #[serde(default="Vertex::default_color")]
+    pub color: [f32; 4],
 }
 
 impl Vertex{
     pub fn new(x: f32, y: f32, z: f32) -> Self {
         Vertex{
-            position: [x, y, z]
+            position: [x, y, z],
+            ..Vertex::default()
         }
     }
+
+    pub fn with_normal(mut self, normal: [f32; 3]) -> Self {
+        self.normal = normal;
+        self
+    }
+
+    pub fn with_uv(mut self, uv: [f32; 2]) -> Self {
+        self.uv = uv;
+        self
+    }
+
+    pub fn with_tangent(mut self, tangent: [f32; 4]) -> Self {
+        self.tangent = tangent;
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn default_normal() -> [f32; 3] {
+        [0.0, 0.0, 1.0]
+    }
+
+    fn default_tangent() -> [f32; 4] {
+        [1.0, 0.0, 0.0, 1.0]
+    }
+
+    fn default_color() -> [f32; 4] {
+        [1.0, 1.0, 1.0, 1.0]
+    }
+}
+
+// facing up, untinted
+impl Default for Vertex{
+    fn default() -> Self {
+        Vertex{
+            position: [0.0, 0.0, 0.0],
+            normal: Vertex::default_normal(),
+            uv: [0.0, 0.0],
+            tangent: Vertex::default_tangent(),
+            color: Vertex::default_color(),
+        }
+    }
+}
+vulkano::impl_vertex!(Vertex, position, normal, uv, tangent, color);
+
+// corners of the fullscreen quads the lighting passes draw, already in clip space
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+pub struct ScreenVertex {
+    pub position: [f32; 2],
+}
+
+impl ScreenVertex{
+    // two triangles covering the whole screen
+    pub fn fullscreen_quad() -> [ScreenVertex; 6] {
+        [
+            ScreenVertex{ position: [-1.0, -1.0] },
+            ScreenVertex{ position: [1.0, -1.0] },
+            ScreenVertex{ position: [1.0, 1.0] },
+            ScreenVertex{ position: [-1.0, -1.0] },
+            ScreenVertex{ position: [1.0, 1.0] },
+            ScreenVertex{ position: [-1.0, 1.0] },
+        ]
+    }
 }
-vulkano::impl_vertex!(Vertex, position);
+vulkano::impl_vertex!(ScreenVertex, position);
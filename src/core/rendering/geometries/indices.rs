@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use vulkano::{
+    buffer::BufferUsage,
+    buffer::ImmutableBuffer,
+    buffer::TypedBufferAccess,
+    command_buffer::AutoCommandBufferBuilder,
+    device::Queue,
+};
+
+use crate::core::rendering::upload_buffer;
+
+// a triangle list's indices, as narrow as the mesh allows. small meshes stay 16 bit to save memory, anything
+// past 65536 vertices goes to 32 bit. written out as a plain list and narrowed again when it's read back in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Indices{
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices{
+    // 16 bit when every index fits
+    pub fn from_u32(indices: Vec<u32>) -> Self {
+        if indices.iter().all(|index| *index <= u16::MAX as u32) {
+            Indices::U16(indices.into_iter().map(|index| index as u16).collect())
+        } else {
+            Indices::U32(indices)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, i: usize) -> Option<u32> {
+        match self {
+            Indices::U16(indices) => indices.get(i).map(|index| *index as u32),
+            Indices::U32(indices) => indices.get(i).cloned(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.len()).map(move |i| self.get(i).unwrap())
+    }
+
+    // every whole triangle. trailing indices that don't make one are skipped
+    pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        (0..self.len() / 3).map(move |i| [
+            self.get(i * 3).unwrap(),
+            self.get(i * 3 + 1).unwrap(),
+            self.get(i * 3 + 2).unwrap(),
+        ])
+    }
+
+    // device local, uploaded once
+    pub fn create_buffer(&self, queue: Arc<Queue>) -> IndexBuffer {
+        match self {
+            Indices::U16(indices) => IndexBuffer::U16(
+                upload_buffer(queue, BufferUsage::index_buffer(), indices.iter().cloned())
+            ),
+            Indices::U32(indices) => IndexBuffer::U32(
+                upload_buffer(queue, BufferUsage::index_buffer(), indices.iter().cloned())
+            ),
+        }
+    }
+}
+
+impl Default for Indices{
+    fn default() -> Self {
+        Indices::U16(Vec::new())
+    }
+}
+
+// an index buffer of whichever width its indices were. binding it tells vulkan which one
+#[derive(Clone)]
+pub enum IndexBuffer{
+    U16(Arc<ImmutableBuffer<[u16]>>),
+    U32(Arc<ImmutableBuffer<[u32]>>),
+}
+
+impl IndexBuffer{
+    pub fn len(&self) -> u64 {
+        match self {
+            IndexBuffer::U16(buffer) => buffer.len(),
+            IndexBuffer::U32(buffer) => buffer.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn bind<'a, L, P>(&self, builder: &'a mut AutoCommandBufferBuilder<L, P>) -> &'a mut AutoCommandBufferBuilder<L, P> {
+        match self {
+            IndexBuffer::U16(buffer) => builder.bind_index_buffer(buffer.clone()),
+            IndexBuffer::U32(buffer) => builder.bind_index_buffer(buffer.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_indices_narrow_to_16_bit() {
+        let indices = Indices::from_u32(vec![0, 1, u16::MAX as u32]);
+        assert_eq!(indices, Indices::U16(vec![0, 1, u16::MAX]));
+    }
+
+    #[test]
+    fn any_index_past_16_bit_keeps_them_all_32_bit() {
+        let indices = Indices::from_u32(vec![0, 1, u16::MAX as u32 + 1]);
+        assert_eq!(indices, Indices::U32(vec![0, 1, u16::MAX as u32 + 1]));
+    }
+
+    #[test]
+    fn no_indices_is_16_bit_and_empty() {
+        let indices = Indices::from_u32(Vec::new());
+        assert_eq!(indices, Indices::U16(Vec::new()));
+        assert!(indices.is_empty());
+    }
+
+    #[test]
+    fn triangles_read_the_same_at_either_width() {
+        let narrow = Indices::from_u32(vec![0, 1, 2, 2, 1, 3, 4]);
+        let wide = Indices::U32(vec![0, 1, 2, 2, 1, 3, 4]);
+        let expected = vec![[0, 1, 2], [2, 1, 3]];
+        assert_eq!(narrow.triangles().collect::<Vec<_>>(), expected);
+        assert_eq!(wide.triangles().collect::<Vec<_>>(), expected);
+        assert_eq!(narrow.iter().collect::<Vec<u32>>(), wide.iter().collect::<Vec<u32>>());
+        assert_eq!(narrow.get(7), None);
+    }
+}
@@ -0,0 +1,392 @@
+use std::fmt;
+use std::path::Path;
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use cgmath::{
+    Deg,
+    InnerSpace,
+    Matrix3,
+    Matrix4,
+    Vector3,
+    Zero,
+};
+
+use crate::core::rendering::geometries::Vertex;
+use crate::core::rendering::geometries::normal_matrix;
+
+// one primitive of a model. its indices are a range of the mesh's index list and already point at the
+// mesh's shared vertex list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubMesh{
+    pub name: String,
+    pub first_index: u32,
+    pub index_count: u32,
+}
+
+// everything in a model file flattened into a single triangle list
+#[derive(Debug, Clone, Default)]
+pub struct MeshData{
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub sub_meshes: Vec<SubMesh>,
+}
+
+#[derive(Debug)]
+pub enum MeshLoadError{
+    Obj(tobj::LoadError),
+    Gltf(gltf::Error),
+    UnsupportedFormat(String),
+    Empty,
+    // the index, and how many vertices there were for it to point at
+    IndexOutOfRange(u32, usize),
+}
+
+impl fmt::Display for MeshLoadError{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshLoadError::Obj(e) => write!(f, "couldn't load obj: {}", e),
+            MeshLoadError::Gltf(e) => write!(f, "couldn't load gltf: {}", e),
+            MeshLoadError::UnsupportedFormat(extension) => write!(f, "unsupported mesh format \"{}\"", extension),
+            MeshLoadError::Empty => write!(f, "file has no triangles in it"),
+            MeshLoadError::IndexOutOfRange(index, count) => write!(f, "index {} is past the end of {} vertices", index, count),
+        }
+    }
+}
+
+impl std::error::Error for MeshLoadError{}
+
+impl From<tobj::LoadError> for MeshLoadError{
+    fn from(e: tobj::LoadError) -> Self {
+        MeshLoadError::Obj(e)
+    }
+}
+
+impl From<gltf::Error> for MeshLoadError{
+    fn from(e: gltf::Error) -> Self {
+        MeshLoadError::Gltf(e)
+    }
+}
+
+impl MeshData{
+    // picks the loader from the file extension. obj, gltf and glb are supported
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MeshLoadError> {
+        let path = path.as_ref();
+        let extension = path.extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let mesh = match extension.as_str() {
+            "obj" => MeshData::load_obj(path)?,
+            "gltf" | "glb" => MeshData::load_gltf(path)?,
+            _ => return Err(MeshLoadError::UnsupportedFormat(extension)),
+        };
+        if mesh.indices.is_empty() {
+            return Err(MeshLoadError::Empty);
+        }
+        Ok(mesh)
+    }
+
+    // every object in the file becomes a sub mesh. faces get triangulated. obj is y up by convention, so
+    // it's stood up on z the same as gltf
+    pub fn load_obj(path: &Path) -> Result<Self, MeshLoadError> {
+        let options = tobj::LoadOptions{
+            single_index: true,
+            triangulate: true,
+            ..Default::default()
+        };
+        let (models, _materials) = tobj::load_obj(path, &options)?;
+
+        let up = y_up_to_z_up();
+        let mut mesh = MeshData::default();
+        for model in models.iter() {
+            let source = &model.mesh;
+            let vertices = (0..source.positions.len() / 3).map(|i| Vertex{
+                position: (up * Vector3::new(source.positions[i * 3], source.positions[i * 3 + 1], source.positions[i * 3 + 2]).extend(1.0)).truncate().into(),
+                normal: if source.normals.len() >= (i + 1) * 3 {
+                    (up * Vector3::new(source.normals[i * 3], source.normals[i * 3 + 1], source.normals[i * 3 + 2]).extend(0.0)).truncate().into()
+                } else {
+                    [0.0; 3]
+                },
+                uv: if source.texcoords.len() >= (i + 1) * 2 {
+                    [source.texcoords[i * 2], source.texcoords[i * 2 + 1]]
+                } else {
+                    [0.0; 2]
+                },
+                color: if source.vertex_color.len() >= (i + 1) * 3 {
+                    [source.vertex_color[i * 3], source.vertex_color[i * 3 + 1], source.vertex_color[i * 3 + 2], 1.0]
+                } else {
+                    [1.0; 4]
+                },
+                ..Vertex::default()
+            }).collect::<Vec<Vertex>>();
+            mesh.push_sub_mesh(model.name.clone(), vertices, &source.indices, source.normals.is_empty(), true)?;
+        }
+        Ok(mesh)
+    }
+
+    // every triangle primitive in the default scene (or the first one) becomes a sub mesh, moved by the
+    // transforms of the nodes it hangs off. gltf is y up, so the whole scene gets stood up on z
+    pub fn load_gltf(path: &Path) -> Result<Self, MeshLoadError> {
+        let (document, buffers, _images) = gltf::import(path)?;
+
+        let mut mesh = MeshData::default();
+        let scene = document.default_scene().or_else(|| document.scenes().next());
+        let roots = match scene {
+            Some(scene) => scene.nodes().map(|node| (node, y_up_to_z_up())).collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+
+        let mut stack = roots;
+        while let Some((node, parent_to_model)) = stack.pop() {
+            let node_to_model = parent_to_model * Matrix4::from(node.transform().matrix());
+            for child in node.children() {
+                stack.push((child, node_to_model));
+            }
+
+            let source = match node.mesh() {
+                Some(source) => source,
+                None => continue,
+            };
+            let normal_matrix = normal_matrix(&node_to_model);
+            let normal_matrix = Matrix3::from_cols(normal_matrix.x.truncate(), normal_matrix.y.truncate(), normal_matrix.z.truncate());
+            // tangents lie along the surface so they move like positions do
+            let tangent_matrix = Matrix3::from_cols(node_to_model.x.truncate(), node_to_model.y.truncate(), node_to_model.z.truncate());
+
+            for (i, primitive) in source.primitives().enumerate() {
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    log::warn!("Skipping primitive {} of mesh {:?}, only triangle lists are supported.", i, source.name());
+                    continue;
+                }
+                let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                let positions = match reader.read_positions() {
+                    Some(positions) => positions.collect::<Vec<[f32; 3]>>(),
+                    None => continue,
+                };
+                let normals = reader.read_normals().map(|normals| normals.collect::<Vec<[f32; 3]>>());
+                let uvs = reader.read_tex_coords(0).map(|uvs| uvs.into_f32().collect::<Vec<[f32; 2]>>());
+                let tangents = reader.read_tangents().map(|tangents| tangents.collect::<Vec<[f32; 4]>>());
+                let colors = reader.read_colors(0).map(|colors| colors.into_rgba_f32().collect::<Vec<[f32; 4]>>());
+
+                let vertices = positions.iter().enumerate().map(|(v, position)| {
+                    let position = (node_to_model * Vector3::from(*position).extend(1.0)).truncate();
+                    let normal = normals.as_ref()
+                        .and_then(|normals| normals.get(v))
+                        .map(|normal| {
+                            let normal = normal_matrix * Vector3::from(*normal);
+                            if normal.magnitude2() > 0.0 { normal.normalize() } else { normal }
+                        })
+                        .unwrap_or(Vector3::zero());
+                    let tangent = tangents.as_ref()
+                        .and_then(|tangents| tangents.get(v))
+                        .map(|tangent| {
+                            let direction = tangent_matrix * Vector3::new(tangent[0], tangent[1], tangent[2]);
+                            let direction = if direction.magnitude2() > 0.0 { direction.normalize() } else { direction };
+                            direction.extend(tangent[3]).into()
+                        })
+                        .unwrap_or([1.0, 0.0, 0.0, 1.0]);
+                    Vertex{
+                        position: position.into(),
+                        normal: normal.into(),
+                        uv: uvs.as_ref().and_then(|uvs| uvs.get(v)).cloned().unwrap_or([0.0; 2]),
+                        tangent: tangent,
+                        color: colors.as_ref().and_then(|colors| colors.get(v)).cloned().unwrap_or([1.0; 4]),
+                    }
+                }).collect::<Vec<Vertex>>();
+                // primitives without indices draw their vertices in order
+                let indices = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect::<Vec<u32>>(),
+                    None => (0..vertices.len() as u32).collect(),
+                };
+
+                let name = format!("{}.{}", source.name().unwrap_or("mesh"), i);
+                mesh.push_sub_mesh(name, vertices, &indices, normals.is_none(), tangents.is_none())?;
+            }
+        }
+        Ok(mesh)
+    }
+
+    // smooth normals from the triangles around each vertex, for meshes that don't come with any
+    pub fn compute_normals(&mut self){
+        compute_normals(&mut self.vertices, &self.indices);
+    }
+
+    // tangents that follow the direction u increases in, for normal mapping
+    pub fn compute_tangents(&mut self){
+        compute_tangents(&mut self.vertices, &self.indices);
+    }
+
+    // model files wind their triangles counter-clockwise, so every triangle gets flipped to clockwise before
+    // anything is worked out from it. normals and tangents the file didn't have are filled in afterwards
+    fn push_sub_mesh(
+        &mut self,
+        name: String,
+        mut vertices: Vec<Vertex>,
+        indices: &[u32],
+        generate_normals: bool,
+        generate_tangents: bool,
+    ) -> Result<(), MeshLoadError> {
+        if let Some(index) = indices.iter().find(|index| **index as usize >= vertices.len()) {
+            return Err(MeshLoadError::IndexOutOfRange(*index, vertices.len()));
+        }
+        // drop any trailing indices that don't make up a whole triangle
+        let indices = indices.chunks_exact(3)
+            .flat_map(|triangle| [triangle[0], triangle[2], triangle[1]])
+            .collect::<Vec<u32>>();
+        if generate_normals {
+            compute_normals(&mut vertices, &indices);
+        }
+        if generate_tangents {
+            compute_tangents(&mut vertices, &indices);
+        }
+
+        let base = self.vertices.len() as u32;
+        let first_index = self.indices.len() as u32;
+        self.vertices.extend(vertices);
+        self.indices.extend(indices.iter().map(|index| index + base));
+        self.sub_meshes.push(SubMesh{
+            name: name,
+            first_index: first_index,
+            index_count: indices.len() as u32,
+        });
+        Ok(())
+    }
+}
+
+// model files are y up, the engine is z up. y goes to z and z to -y
+fn y_up_to_z_up() -> Matrix4<f32> {
+    Matrix4::from_angle_x(Deg(90.0))
+}
+
+// area weighted average of the faces each vertex is part of. faces wind clockwise seen from outside, the same
+// as everything else the engine draws
+fn compute_normals(vertices: &mut [Vertex], indices: &[u32]){
+    let mut normals = vec![Vector3::zero(); vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let (a, b, c) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+        if a >= vertices.len() || b >= vertices.len() || c >= vertices.len() {
+            continue;
+        }
+        let position = |i: usize| Vector3::from(vertices[i].position);
+        let face = (position(c) - position(a)).cross(position(b) - position(a));
+        normals[a] += face;
+        normals[b] += face;
+        normals[c] += face;
+    }
+    for (vertex, normal) in vertices.iter_mut().zip(normals.into_iter()) {
+        vertex.normal = if normal.magnitude2() > 0.0 {
+            normal.normalize().into()
+        } else {
+            [0.0, 0.0, 1.0]
+        };
+    }
+}
+
+// accumulates the uv directions of every triangle onto its vertices then makes them perpendicular to the
+// normal. vertices without usable uvs get any tangent that's perpendicular
+fn compute_tangents(vertices: &mut [Vertex], indices: &[u32]){
+    let mut tangents = vec![Vector3::zero(); vertices.len()];
+    let mut bitangents = vec![Vector3::zero(); vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let (a, b, c) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+        if a >= vertices.len() || b >= vertices.len() || c >= vertices.len() {
+            continue;
+        }
+        let position = |i: usize| Vector3::from(vertices[i].position);
+        let uv = |i: usize| vertices[i].uv;
+        let edge_1 = position(b) - position(a);
+        let edge_2 = position(c) - position(a);
+        let (du_1, dv_1) = (uv(b)[0] - uv(a)[0], uv(b)[1] - uv(a)[1]);
+        let (du_2, dv_2) = (uv(c)[0] - uv(a)[0], uv(c)[1] - uv(a)[1]);
+        let determinant = du_1 * dv_2 - du_2 * dv_1;
+        if determinant.abs() < f32::EPSILON {
+            continue;
+        }
+        let tangent = (edge_1 * dv_2 - edge_2 * dv_1) / determinant;
+        let bitangent = (edge_2 * du_1 - edge_1 * du_2) / determinant;
+        for i in [a, b, c].iter() {
+            tangents[*i] += tangent;
+            bitangents[*i] += bitangent;
+        }
+    }
+    for (i, vertex) in vertices.iter_mut().enumerate() {
+        let normal = Vector3::from(vertex.normal);
+        let mut tangent = tangents[i] - normal * normal.dot(tangents[i]);
+        if tangent.magnitude2() < f32::EPSILON {
+            let axis = if normal.x.abs() < 0.9 { Vector3::unit_x() } else { Vector3::unit_y() };
+            tangent = axis - normal * normal.dot(axis);
+        }
+        let tangent = tangent.normalize();
+        let handedness = if normal.cross(tangent).dot(bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+        vertex.tangent = tangent.extend(handedness).into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a cube around the origin with no normals, faces counter-clockwise seen from outside like exporters write
+    const CUBE_OBJ: &str = "\
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 4 8 7 3
+f 1 5 8 4
+f 2 3 7 6
+";
+
+    fn load_cube() -> MeshData {
+        let path = std::env::temp_dir().join(format!("leaf_cube_{}.obj", std::process::id()));
+        std::fs::write(&path, CUBE_OBJ).unwrap();
+        let mesh = MeshData::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        mesh.unwrap()
+    }
+
+    #[test]
+    fn loaded_triangles_wind_clockwise_from_outside() {
+        let mesh = load_cube();
+        assert_eq!(mesh.indices.len(), 36);
+        for triangle in mesh.indices.chunks_exact(3) {
+            let position = |i: u32| Vector3::from(mesh.vertices[i as usize].position);
+            let (a, b, c) = (position(triangle[0]), position(triangle[1]), position(triangle[2]));
+            let face = (c - a).cross(b - a);
+            let centroid = (a + b + c) / 3.0;
+            assert!(face.dot(centroid) > 0.0);
+        }
+    }
+
+    #[test]
+    fn generated_normals_point_out_of_the_cube() {
+        let mesh = load_cube();
+        for vertex in mesh.vertices.iter() {
+            let normal = Vector3::from(vertex.normal);
+            assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+            // the cube is centered on the origin, so outward is along the position
+            assert!(normal.dot(Vector3::from(vertex.position)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn out_of_range_indices_are_rejected() {
+        let vertices = vec![Vertex::new(0.0, 0.0, 0.0), Vertex::new(1.0, 0.0, 0.0), Vertex::new(0.0, 1.0, 0.0)];
+        let mut mesh = MeshData::default();
+        match mesh.push_sub_mesh("bad".to_string(), vertices, &[0, 1, 3], true, true) {
+            Err(MeshLoadError::IndexOutOfRange(3, 3)) => (),
+            other => panic!("expected an out of range index, got {:?}", other),
+        }
+        assert!(mesh.vertices.is_empty());
+    }
+}
@@ -1,5 +1,20 @@
 pub mod geometry_primitives;
 pub mod terrain;
+pub mod bounds;
+pub mod ray;
+pub mod mesh;
+pub mod procedural;
+pub mod indices;
+pub mod transform;
 
 pub use geometry_primitives::Vertex;
-pub use terrain::TerrainGeometry;
\ No newline at end of file
+pub use geometry_primitives::ScreenVertex;
+pub use terrain::TerrainGeometry;
+pub use bounds::BoundingVolume;
+pub use bounds::Frustum;
+pub use ray::Ray;
+pub use mesh::MeshData;
+pub use mesh::SubMesh;
+pub use indices::Indices;
+pub use indices::IndexBuffer;
+pub use transform::normal_matrix;
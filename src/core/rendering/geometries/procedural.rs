@@ -0,0 +1,350 @@
+use std::f32::consts::PI;
+
+use cgmath::{
+    InnerSpace,
+    Vector3,
+};
+
+use crate::core::rendering::geometries::mesh::{
+    MeshData,
+    SubMesh,
+};
+use crate::core::rendering::geometries::Vertex;
+
+// a point on the outline a surface of revolution is swept from. normal is in the same (radius, z) plane
+#[derive(Debug, Clone, Copy)]
+struct ProfilePoint{
+    radius: f32,
+    z: f32,
+    normal: [f32; 2],
+}
+
+// shapes are centered on the origin with z up. triangles wind clockwise seen from outside, the same as the
+// hand built cube, so they survive back face culling
+impl MeshData{
+    pub fn cuboid(half_extents: Vector3<f32>) -> Self {
+        let mut mesh = MeshData::default();
+        let h = half_extents;
+        // normal, then two axes across the face
+        let faces = [
+            (Vector3::unit_z(), Vector3::unit_x(), Vector3::unit_y()),
+            (-Vector3::unit_z(), Vector3::unit_x(), Vector3::unit_y()),
+            (Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()),
+            (-Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()),
+            (Vector3::unit_y(), Vector3::unit_x(), Vector3::unit_z()),
+            (-Vector3::unit_y(), Vector3::unit_x(), Vector3::unit_z()),
+        ];
+        for (normal, across, up) in faces.iter() {
+            let center = multiply(*normal, h);
+            let across_extent = multiply(*across, h);
+            let up_extent = multiply(*up, h);
+            let base = mesh.vertices.len() as u32;
+            for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].iter() {
+                let position = center + across_extent * (u * 2.0 - 1.0) + up_extent * (v * 2.0 - 1.0);
+                mesh.vertices.push(Vertex{
+                    position: position.into(),
+                    normal: (*normal).into(),
+                    uv: [*u, *v],
+                    ..Vertex::default()
+                });
+            }
+            mesh.push_outward_triangle(base, base + 1, base + 2);
+            mesh.push_outward_triangle(base, base + 2, base + 3);
+        }
+        mesh.finish("cuboid")
+    }
+
+    // flat on the xy plane facing up
+    pub fn triangle(size: f32) -> Self {
+        let half = size * 0.5;
+        let corners = [([-half, -half, 0.0], [0.0, 0.0]), ([0.0, half, 0.0], [0.5, 1.0]), ([half, -half, 0.0], [1.0, 0.0])];
+        let mut mesh = MeshData::default();
+        for (position, uv) in corners.iter() {
+            mesh.vertices.push(Vertex{
+                position: *position,
+                normal: [0.0, 0.0, 1.0],
+                uv: *uv,
+                ..Vertex::default()
+            });
+        }
+        mesh.push_outward_triangle(0, 1, 2);
+        mesh.finish("triangle")
+    }
+
+    // flat on the xy plane facing up, size on a side, split into subdivisions squares each way
+    pub fn plane(size: f32, subdivisions: u32) -> Self {
+        let subdivisions = subdivisions.max(1);
+        let mut mesh = MeshData::default();
+        mesh.push_grid(subdivisions, subdivisions, |u, v| {
+            (Vector3::new((u - 0.5) * size, (v - 0.5) * size, 0.0), Vector3::unit_z())
+        });
+        mesh.finish("plane")
+    }
+
+    // segments around, rings from pole to pole
+    pub fn sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let rings = rings.max(2);
+        let profile = (0..=rings).map(|ring| {
+            let angle = PI * (1.0 - ring as f32 / rings as f32);
+            ProfilePoint{
+                radius: radius * angle.sin(),
+                z: radius * angle.cos(),
+                normal: [angle.sin(), angle.cos()],
+            }
+        }).collect::<Vec<_>>();
+        let mut mesh = MeshData::default();
+        mesh.push_revolution(segments, &profile);
+        mesh.finish("sphere")
+    }
+
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Self {
+        let half = height * 0.5;
+        let mut mesh = MeshData::default();
+        mesh.push_revolution(segments, &[
+            ProfilePoint{radius: radius, z: -half, normal: [1.0, 0.0]},
+            ProfilePoint{radius: radius, z: half, normal: [1.0, 0.0]},
+        ]);
+        mesh.push_cap(segments, radius, -half, false);
+        mesh.push_cap(segments, radius, half, true);
+        mesh.finish("cylinder")
+    }
+
+    // base on the bottom, point at the top
+    pub fn cone(radius: f32, height: f32, segments: u32) -> Self {
+        let half = height * 0.5;
+        let slant = Vector3::new(height, 0.0, radius).normalize();
+        let normal = [slant.x, slant.z];
+        let mut mesh = MeshData::default();
+        mesh.push_revolution(segments, &[
+            ProfilePoint{radius: radius, z: -half, normal: normal},
+            ProfilePoint{radius: 0.0, z: half, normal: normal},
+        ]);
+        mesh.push_cap(segments, radius, -half, false);
+        mesh.finish("cone")
+    }
+
+    // ring lying on the xy plane. radius is to the middle of the tube, sides go around the tube
+    pub fn torus(radius: f32, tube_radius: f32, segments: u32, sides: u32) -> Self {
+        let sides = sides.max(3);
+        let profile = (0..=sides).map(|side| {
+            let angle = 2.0 * PI * side as f32 / sides as f32;
+            ProfilePoint{
+                radius: radius + tube_radius * angle.cos(),
+                z: tube_radius * angle.sin(),
+                normal: [angle.cos(), angle.sin()],
+            }
+        }).collect::<Vec<_>>();
+        let mut mesh = MeshData::default();
+        mesh.push_revolution(segments, &profile);
+        mesh.finish("torus")
+    }
+
+    // height is the straight part between the two half spheres, rings are per half sphere
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Self {
+        let rings = rings.max(1);
+        let half = height * 0.5;
+        let mut profile = Vec::new();
+        // bottom pole up to the equator, then the equator again at the top of the straight part up to the top pole
+        for ring in 0..=rings {
+            let angle = PI * (1.0 - 0.5 * ring as f32 / rings as f32);
+            profile.push(ProfilePoint{
+                radius: radius * angle.sin(),
+                z: radius * angle.cos() - half,
+                normal: [angle.sin(), angle.cos()],
+            });
+        }
+        for ring in 0..=rings {
+            let angle = PI * 0.5 * (1.0 - ring as f32 / rings as f32);
+            profile.push(ProfilePoint{
+                radius: radius * angle.sin(),
+                z: radius * angle.cos() + half,
+                normal: [angle.sin(), angle.cos()],
+            });
+        }
+        let mut mesh = MeshData::default();
+        mesh.push_revolution(segments, &profile);
+        mesh.finish("capsule")
+    }
+
+    // winds a triangle clockwise seen from the side its vertex normals face
+    fn push_outward_triangle(&mut self, a: u32, b: u32, c: u32){
+        let position = |index: u32| Vector3::from(self.vertices[index as usize].position);
+        let normal = |index: u32| Vector3::from(self.vertices[index as usize].normal);
+        let face = (position(b) - position(a)).cross(position(c) - position(a));
+        let outward = normal(a) + normal(b) + normal(c);
+        if face.dot(outward) > 0.0 {
+            self.indices.extend_from_slice(&[a, c, b]);
+        } else {
+            self.indices.extend_from_slice(&[a, b, c]);
+        }
+    }
+
+    // (columns + 1) by (rows + 1) vertices from a function of uv, two triangles per cell. closed shapes get
+    // their seam twice so both sides of it have their own uvs
+    fn push_grid<F>(&mut self, columns: u32, rows: u32, surface: F)
+    where
+        F: Fn(f32, f32) -> (Vector3<f32>, Vector3<f32>),
+    {
+        let base = self.vertices.len() as u32;
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let u = column as f32 / columns as f32;
+                let v = row as f32 / rows as f32;
+                let (position, normal) = surface(u, v);
+                self.vertices.push(Vertex{
+                    position: position.into(),
+                    normal: normal.into(),
+                    uv: [u, v],
+                    ..Vertex::default()
+                });
+            }
+        }
+        let stride = columns + 1;
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = base + row * stride + column;
+                let b = a + 1;
+                let c = a + stride + 1;
+                let d = a + stride;
+                self.push_outward_triangle(a, b, c);
+                self.push_outward_triangle(a, c, d);
+            }
+        }
+    }
+
+    // sweeps a profile all the way around z
+    fn push_revolution(&mut self, segments: u32, profile: &[ProfilePoint]){
+        let segments = segments.max(3);
+        if profile.len() < 2 {
+            return;
+        }
+        let rows = profile.len() as u32 - 1;
+        self.push_grid(segments, rows, |u, v| {
+            let point = profile[(v * rows as f32).round() as usize];
+            let angle = 2.0 * PI * u;
+            let (sin, cos) = angle.sin_cos();
+            (
+                Vector3::new(point.radius * cos, point.radius * sin, point.z),
+                Vector3::new(point.normal[0] * cos, point.normal[0] * sin, point.normal[1]),
+            )
+        });
+    }
+
+    // flat disc at height z facing up or down
+    fn push_cap(&mut self, segments: u32, radius: f32, z: f32, up: bool){
+        let segments = segments.max(3);
+        let normal = if up { Vector3::unit_z() } else { -Vector3::unit_z() };
+        let center = self.vertices.len() as u32;
+        self.vertices.push(Vertex{
+            position: [0.0, 0.0, z],
+            normal: normal.into(),
+            uv: [0.5, 0.5],
+            ..Vertex::default()
+        });
+        for segment in 0..=segments {
+            let angle = 2.0 * PI * segment as f32 / segments as f32;
+            let (sin, cos) = angle.sin_cos();
+            self.vertices.push(Vertex{
+                position: [radius * cos, radius * sin, z],
+                normal: normal.into(),
+                uv: [0.5 + 0.5 * cos, 0.5 + 0.5 * sin],
+                ..Vertex::default()
+            });
+        }
+        for segment in 0..segments {
+            self.push_outward_triangle(center, center + 1 + segment, center + 2 + segment);
+        }
+    }
+
+    fn finish(mut self, name: &str) -> Self {
+        self.compute_tangents();
+        self.sub_meshes = vec![SubMesh{
+            name: name.to_string(),
+            first_index: 0,
+            index_count: self.indices.len() as u32,
+        }];
+        self
+    }
+}
+
+fn multiply(a: Vector3<f32>, b: Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(a.x * b.x, a.y * b.y, a.z * b.z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // which way is out at a point on a shape
+    type Outward = fn(Vector3<f32>) -> Vector3<f32>;
+
+    // everything but the torus and plane is convex around the origin
+    fn from_center(point: Vector3<f32>) -> Vector3<f32> {
+        point
+    }
+
+    // away from the middle of a tube going around a ring of radius 1
+    fn from_tube(point: Vector3<f32>) -> Vector3<f32> {
+        point - Vector3::new(point.x, point.y, 0.0).normalize()
+    }
+
+    fn up(_: Vector3<f32>) -> Vector3<f32> {
+        Vector3::unit_z()
+    }
+
+    fn shapes() -> Vec<(MeshData, Outward)> {
+        vec![
+            (MeshData::sphere(1.0, 16, 8), from_center as Outward),
+            (MeshData::cylinder(0.5, 2.0, 12), from_center as Outward),
+            (MeshData::cone(1.0, 2.0, 12), from_center as Outward),
+            (MeshData::capsule(0.5, 1.0, 12, 4), from_center as Outward),
+            (MeshData::torus(1.0, 0.25, 16, 8), from_tube as Outward),
+            (MeshData::plane(2.0, 4), up as Outward),
+        ]
+    }
+
+    fn position(mesh: &MeshData, index: u32) -> Vector3<f32> {
+        Vector3::from(mesh.vertices[index as usize].position)
+    }
+
+    #[test]
+    fn triangles_wind_clockwise_from_outside() {
+        for (mesh, outward) in shapes() {
+            let name = &mesh.sub_meshes[0].name;
+            assert!(!mesh.indices.is_empty(), "{} has no triangles", name);
+            assert_eq!(mesh.indices.len() % 3, 0);
+            for triangle in mesh.indices.chunks(3) {
+                let (a, b, c) = (position(&mesh, triangle[0]), position(&mesh, triangle[1]), position(&mesh, triangle[2]));
+                // the rows at the poles collapse to a point, those triangles have no side to face
+                let face = (b - a).cross(c - a);
+                if face.magnitude() < 1e-6 {
+                    continue;
+                }
+                // counter clockwise would face along outward
+                let center = (a + b + c) / 3.0;
+                assert!(face.dot(outward(center)) < 0.0, "{} triangle {:?} winds counter clockwise", name, triangle);
+            }
+        }
+    }
+
+    #[test]
+    fn normals_are_unit_length_and_point_out() {
+        for (mesh, outward) in shapes() {
+            let name = &mesh.sub_meshes[0].name;
+            for vertex in mesh.vertices.iter() {
+                let normal = Vector3::from(vertex.normal);
+                assert!((normal.magnitude() - 1.0).abs() < 1e-5, "{} normal {:?} isn't unit length", name, normal);
+                assert!(normal.dot(outward(Vector3::from(vertex.position))) > 0.0, "{} normal {:?} points in", name, normal);
+            }
+        }
+    }
+
+    #[test]
+    fn sub_mesh_covers_every_index() {
+        for (mesh, _) in shapes() {
+            assert_eq!(mesh.sub_meshes.len(), 1);
+            assert_eq!(mesh.sub_meshes[0].index_count as usize, mesh.indices.len());
+            assert!(mesh.indices.iter().all(|index| (*index as usize) < mesh.vertices.len()));
+        }
+    }
+}
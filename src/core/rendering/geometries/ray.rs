@@ -0,0 +1,129 @@
+use cgmath::{
+    InnerSpace,
+    Matrix4,
+    Vector3,
+};
+
+use crate::core::rendering::geometries::Vertex;
+use crate::core::rendering::geometries::Indices;
+
+// a half line. direction doesn't have to be normalized, distances along the ray are in multiples of it so
+// a hit found on a transformed ray is at the same t on the original
+#[derive(Debug, Clone, Copy)]
+pub struct Ray{
+    pub origin: Vector3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Ray{
+    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
+        Ray{
+            origin: origin,
+            direction: direction,
+        }
+    }
+
+    pub fn at(&self, t: f32) -> Vector3<f32> {
+        self.origin + (self.direction * t)
+    }
+
+    pub fn transformed(&self, matrix: &Matrix4<f32>) -> Self {
+        Ray{
+            origin: (matrix * self.origin.extend(1.0)).truncate(),
+            direction: (matrix * self.direction.extend(0.0)).truncate(),
+        }
+    }
+
+    pub fn intersect_sphere(&self, center: Vector3<f32>, radius: f32) -> Option<f32> {
+        let to_origin = self.origin - center;
+        let a = self.direction.dot(self.direction);
+        let b = 2.0 * to_origin.dot(self.direction);
+        let c = to_origin.dot(to_origin) - (radius * radius);
+        let discriminant = (b * b) - (4.0 * a * c);
+        if discriminant < 0.0 || a == 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let near = (-b - root) / (2.0 * a);
+        let far = (-b + root) / (2.0 * a);
+        // starting inside the sphere counts as hitting it right away
+        if far < 0.0 {
+            None
+        } else {
+            Some(near.max(0.0))
+        }
+    }
+
+    // slab test
+    pub fn intersect_aabb(&self, min: Vector3<f32>, max: Vector3<f32>) -> Option<f32> {
+        self.clip_aabb(min, max).map(|(t_min, _)| t_min)
+    }
+
+    // the stretch of the ray inside a box, as the t it goes in at and the t it comes out at
+    pub fn clip_aabb(&self, min: Vector3<f32>, max: Vector3<f32>) -> Option<(f32, f32)> {
+        let mut t_min = 0.0f32;
+        let mut t_max = f32::INFINITY;
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            let direction = self.direction[axis];
+            if direction.abs() < 1e-8 {
+                if origin < min[axis] || origin > max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (min[axis] - origin) / direction;
+            let t1 = (max[axis] - origin) / direction;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_min > t_max {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    // moller trumbore. hits triangles from either side
+    pub fn intersect_triangle(&self, a: Vector3<f32>, b: Vector3<f32>, c: Vector3<f32>) -> Option<f32> {
+        let edge_ab = b - a;
+        let edge_ac = c - a;
+        let p = self.direction.cross(edge_ac);
+        let determinant = edge_ab.dot(p);
+        if determinant.abs() < 1e-8 {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+        let to_origin = self.origin - a;
+        let u = to_origin.dot(p) * inverse;
+        if u < 0.0 || u > 1.0 {
+            return None;
+        }
+        let q = to_origin.cross(edge_ab);
+        let v = self.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = edge_ac.dot(q) * inverse;
+        if t < 0.0 {
+            None
+        } else {
+            Some(t)
+        }
+    }
+
+    // nearest hit against an indexed triangle list. trailing indices that don't make a full triangle are ignored
+    pub fn intersect_mesh(&self, vertices: &[Vertex], indices: &Indices) -> Option<f32> {
+        let position = |index: u32| vertices.get(index as usize).map(|vertex| Vector3::from(vertex.position));
+        indices.triangles()
+            .filter_map(|triangle| {
+                let a = position(triangle[0])?;
+                let b = position(triangle[1])?;
+                let c = position(triangle[2])?;
+                self.intersect_triangle(a, b, c)
+            })
+            .fold(None, |nearest: Option<f32>, t| match nearest {
+                Some(nearest) if nearest <= t => Some(nearest),
+                _ => Some(t),
+            })
+    }
+}
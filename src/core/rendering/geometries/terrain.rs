@@ -4,37 +4,56 @@ use std::sync::{
 use std::borrow::Borrow;
 
 use crate::core::rendering::geometries::Vertex;
-use crate::core::plugins::components::GeometryComponent;
+use crate::core::rendering::geometries::BoundingVolume;
+use crate::core::rendering::geometries::Ray;
+use crate::core::rendering::geometries::{
+    Indices,
+    IndexBuffer,
+};
+use crate::core::rendering::upload_buffer;
+use crate::core::physics::heightfield::{
+    bilinear_height,
+    bilinear_normal,
+};
 
-use vulkano::buffer::CpuAccessibleBuffer;
+use vulkano::buffer::ImmutableBuffer;
 use vulkano::buffer::BufferUsage;
-use vulkano::device::Device;
+use vulkano::device::Queue;
 
+use cgmath::{
+    InnerSpace,
+    Vector3,
+};
 use noise::{NoiseFn, OpenSimplex};
 use serde::{Serialize, Deserialize};
 
 #[derive(Serialize, Deserialize)]
 pub struct TerrainGeometry{
     pub vertices: Vec<Vertex>,
-    pub indices: Vec<u16>,
+    pub indices: Indices,
     pub height_map: Vec<Vec<f64>>,
     pub size: usize,
     pub amplitude: f64,
     pub seed: u32,
     #[serde(skip, default="TerrainGeometry::default_noise_fn")]
     pub noise_fn: Box<dyn NoiseFn<[f64; 2]> + Send + Sync>,
-    #[serde(skip, default="GeometryComponent::default_vertex_buffer")]
-    pub vertex_buffer: Option<Arc<CpuAccessibleBuffer<[Vertex]>>>,
-    #[serde(skip, default="GeometryComponent::default_index_buffer")]
-    pub index_buffer: Option<Arc<CpuAccessibleBuffer<[u16]>>>,
+    #[serde(skip, default="TerrainGeometry::default_vertex_buffer")]
+    pub vertex_buffer: Option<Arc<ImmutableBuffer<[Vertex]>>>,
+    #[serde(skip, default="TerrainGeometry::default_index_buffer")]
+    pub index_buffer: Option<IndexBuffer>,
     pub initialized: bool,
+    #[serde(default)]
+    pub bounds: BoundingVolume,
+    // bumped every time the terrain is regenerated, so anything copied from it can tell it's out of date
+    #[serde(skip)]
+    pub generation: u64,
 }
 
 impl TerrainGeometry{
     pub fn new(size: usize) -> Self{
         TerrainGeometry{
             vertices: Vec::new(),
-            indices: Vec::new(),
+            indices: Indices::default(),
             height_map: Vec::new(),
             size: size,
             amplitude: 1.0,
@@ -42,7 +61,9 @@ impl TerrainGeometry{
             noise_fn: Box::new(OpenSimplex::new()),
             vertex_buffer: None,
             index_buffer: None,
-            initialized: false
+            initialized: false,
+            bounds: BoundingVolume::default(),
+            generation: 0,
         }
     }
 
@@ -54,73 +75,169 @@ impl TerrainGeometry{
 
     pub fn generate_terrain(&mut self){
         self.height_map.clear();
-        let size = self.size as u16;
+        let size = self.size;
         self.vertices.clear();
-        self.indices.clear();
         let noise_fn: &(dyn NoiseFn<[f64; 2]> + Send + Sync) = self.noise_fn.borrow();
-        let mut i = 0;
         for x in 0..size {
+            let mut column = Vec::with_capacity(size);
             for y in 0..size {
                 let noise = noise_fn.get([x as f64, y as f64]);
-                let z = (noise * self.amplitude) as f32;
+                column.push(noise * self.amplitude);
+            }
+            // height_map[x][y] is the height at (x, y)
+            self.height_map.push(column);
+        }
+
+        // uvs stretch once over the whole terrain
+        let uv_scale = 1.0 / (size.max(2) - 1) as f32;
+        for x in 0..size {
+            for y in 0..size {
+                let (normal, slope_x) = self.vertex_normal(x, y);
+                let tangent = Vector3::new(1.0, 0.0, slope_x).normalize();
                 self.vertices.push(
-                    Vertex{
-                        position: [x as f32, y as f32, z as f32]
-                    }
+                    Vertex::new(x as f32, y as f32, self.height_map[x][y] as f32)
+                        .with_normal(normal.into())
+                        .with_uv([x as f32 * uv_scale, y as f32 * uv_scale])
+                        .with_tangent(tangent.extend(1.0).into())
                 );
-                i = i + 1;
             }
         }
 
-        for y in 0..(size-1) {
-            for x in 0..(size-1) {
-                let ix = (y * size + x) as u16;
-                self.indices.push(ix);
-                self.indices.push(ix + 1);
-                self.indices.push(ix + size + 1);
+        // goes 32 bit by itself once the grid is past 256 on a side
+        let stride = size as u32;
+        let mut indices = Vec::with_capacity(size.saturating_sub(1).pow(2) * 6);
+        for y in 0..stride.saturating_sub(1) {
+            for x in 0..stride.saturating_sub(1) {
+                let ix = y * stride + x;
+                indices.push(ix);
+                indices.push(ix + 1);
+                indices.push(ix + stride + 1);
 
-                self.indices.push(ix + size);
-                self.indices.push(ix);
-                self.indices.push(ix + size + 1);
+                indices.push(ix + stride);
+                indices.push(ix);
+                indices.push(ix + stride + 1);
 
             }
         }
+        self.indices = Indices::from_u32(indices);
+        self.bounds = BoundingVolume::from_vertices(&self.vertices);
+        self.generation += 1;
+    }
+
+    // model space, bilinear between the samples around (x, y). None off the edge of the terrain or before
+    // it's been generated
+    pub fn height_at(&self, x: f32, y: f32) -> Option<f32> {
+        bilinear_height(self.height_map_size(), |x, y| self.height_map[x][y] as f32, x, y)
+    }
+
+    pub fn normal_at(&self, x: f32, y: f32) -> Option<Vector3<f32>> {
+        bilinear_normal(self.height_map_size(), |x, y| self.height_map[x][y] as f32, x, y)
+    }
+
+    // where a model space ray first comes down onto the surface. walks the ray half a cell at a time and
+    // narrows down the step it went under in, so the cost follows how far the ray crosses the terrain rather
+    // than how many triangles there are
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        let (t_start, t_end) = ray.clip_aabb(self.bounds.min, self.bounds.max)?;
+        // how far above the surface the ray is at t. clamped so rounding at the edges stays on the grid
+        let above = |t: f32| {
+            let point = ray.at(t);
+            let x = point.x.clamp(self.bounds.min.x, self.bounds.max.x);
+            let y = point.y.clamp(self.bounds.min.y, self.bounds.max.y);
+            self.height_at(x, y).map(|height| point.z - height)
+        };
+
+        let horizontal = ray.direction.x.abs().max(ray.direction.y.abs());
+        let step = if horizontal > 1e-8 { 0.5 / horizontal } else { t_end - t_start };
+        let mut t = t_start;
+        if above(t)? <= 0.0 {
+            return Some(t);
+        }
+        while t < t_end {
+            let next = (t + step).min(t_end);
+            if above(next)? <= 0.0 {
+                let (mut high, mut low) = (t, next);
+                for _ in 0..20 {
+                    let middle = (high + low) * 0.5;
+                    if above(middle).map_or(false, |height| height > 0.0) {
+                        high = middle;
+                    } else {
+                        low = middle;
+                    }
+                }
+                return Some(low);
+            }
+            t = next;
+        }
+        None
+    }
+
+    // central differences between the neighbouring samples, one sided at the edges. also hands back the
+    // slope along x for the tangent
+    fn vertex_normal(&self, x: usize, y: usize) -> (Vector3<f32>, f32) {
+        let (width, depth) = self.height_map_size();
+        let height = |x: usize, y: usize| self.height_map[x][y] as f32;
+        let (x0, x1) = (x.saturating_sub(1), (x + 1).min(width - 1));
+        let (y0, y1) = (y.saturating_sub(1), (y + 1).min(depth - 1));
+        let dx = if x1 > x0 { (height(x1, y) - height(x0, y)) / (x1 - x0) as f32 } else { 0.0 };
+        let dy = if y1 > y0 { (height(x, y1) - height(x, y0)) / (y1 - y0) as f32 } else { 0.0 };
+        (Vector3::new(-dx, -dy, 1.0).normalize(), dx)
+    }
+
+    fn height_map_size(&self) -> (usize, usize) {
+        (self.height_map.len(), self.height_map.first().map_or(0, |column| column.len()))
     }
 
     pub fn set_noise_fn(&mut self, noise_fn: Box<dyn NoiseFn<[f64;2 ]> + Send + Sync>) {
         self.noise_fn = noise_fn;
     }
 
-    pub fn initialize(&mut self, device: Arc<Device>){
+    // uploads the generated terrain into device local buffers
+    pub fn initialize(&mut self, queue: Arc<Queue>){
         // Vertex buffer init
-        let vertex_buffer = {
-            CpuAccessibleBuffer::from_iter(
-                device.clone(),
-                BufferUsage::all(),
-                false,
-                self.vertices.clone()
-                .iter()
-                .cloned(),
-            )
-            .unwrap()
-        };
+        let vertex_buffer = upload_buffer(queue.clone(), BufferUsage::vertex_buffer(), self.vertices.iter().cloned());
 
         // index buffer init
-        let index_buffer = CpuAccessibleBuffer::from_iter(
-            device.clone(),
-            BufferUsage::all(),
-            false,
-            self.indices.clone()
-            .iter()
-            .cloned(),
-        ).unwrap();
+        let index_buffer = self.indices.create_buffer(queue.clone());
 
         self.vertex_buffer = Some(vertex_buffer);
         self.index_buffer = Some(index_buffer);
         self.initialized = true;
     }
 
+    fn default_vertex_buffer() -> Option<Arc<ImmutableBuffer<[Vertex]>>> {
+        None
+    }
+
+    fn default_index_buffer() -> Option<IndexBuffer> {
+        None
+    }
+
     fn default_noise_fn() -> Box<dyn NoiseFn<[f64; 2]> + Send + Sync>{
         Box::new(OpenSimplex::new())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_terrain_switches_to_32_bit_indices() {
+        let size = 300;
+        let mut terrain = TerrainGeometry::new(size);
+        terrain.generate_terrain();
+        assert_eq!(terrain.vertices.len(), size * size);
+        assert_eq!(terrain.indices.len(), (size - 1) * (size - 1) * 6);
+        match &terrain.indices {
+            Indices::U32(indices) => assert!(indices.iter().all(|index| (*index as usize) < terrain.vertices.len())),
+            Indices::U16(_) => panic!("a {0}x{0} terrain doesn't fit 16 bit indices", size),
+        }
+    }
+
+    #[test]
+    fn small_terrain_stays_16_bit() {
+        let mut terrain = TerrainGeometry::new(256);
+        terrain.generate_terrain();
+        assert!(matches!(terrain.indices, Indices::U16(_)));
+    }
+}
@@ -0,0 +1,14 @@
+use cgmath::{
+    Matrix,
+    Matrix4,
+    SquareMatrix,
+};
+
+// what normals get multiplied by to go the same way as a model_to_world matrix. it's the inverse transpose so
+// non uniform scale doesn't bend them. a matrix that squashes everything flat has no inverse, normals are left
+// alone then
+pub fn normal_matrix(model_to_world: &Matrix4<f32>) -> Matrix4<f32> {
+    model_to_world.invert()
+        .map(|inverse| inverse.transpose())
+        .unwrap_or_else(Matrix4::identity)
+}
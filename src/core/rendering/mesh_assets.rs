@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bevy_ecs::entity::Entity;
+use vulkano::{
+    buffer::BufferUsage,
+    buffer::ImmutableBuffer,
+    device::Queue,
+};
+
+use crate::core::rendering::upload_buffer;
+
+use crate::core::rendering::geometries::{
+    BoundingVolume,
+    Indices,
+    IndexBuffer,
+    MeshData,
+    SubMesh,
+    Vertex,
+};
+
+// points at a mesh in the scene's MeshAssets. only means anything to the store that handed it out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MeshHandle(u32);
+
+// a mesh that's been uploaded. the cpu side copy sticks around for picking
+pub struct MeshAsset{
+    pub vertices: Vec<Vertex>,
+    pub indices: Indices,
+    pub sub_meshes: Vec<SubMesh>,
+    pub bounds: BoundingVolume,
+    pub vertex_buffer: Arc<ImmutableBuffer<[Vertex]>>,
+    pub index_buffer: IndexBuffer,
+}
+
+impl MeshAsset{
+    pub fn upload(mesh: MeshData, queue: Arc<Queue>) -> Self {
+        let vertex_buffer = upload_buffer(queue.clone(), BufferUsage::vertex_buffer(), mesh.vertices.iter().cloned());
+        let indices = Indices::from_u32(mesh.indices);
+        let index_buffer = indices.create_buffer(queue.clone());
+
+        MeshAsset{
+            bounds: BoundingVolume::from_vertices(&mesh.vertices),
+            vertices: mesh.vertices,
+            indices: indices,
+            sub_meshes: mesh.sub_meshes,
+            vertex_buffer: vertex_buffer,
+            index_buffer: index_buffer,
+        }
+    }
+}
+
+struct MeshEntry<A>{
+    asset: A,
+    // what it was loaded as, so the next load of the same thing can share it
+    key: Option<String>,
+    references: usize,
+}
+
+// every mesh a scene has on the gpu. meshes loaded under the same key are shared, and a mesh's buffers get
+// dropped once nothing references it. entities hand their reference back when their geometry goes away.
+// the counting doesn't care what's being counted, so it can be checked without a gpu
+pub struct MeshAssets<A = MeshAsset>{
+    meshes: HashMap<MeshHandle, MeshEntry<A>>,
+    keys: HashMap<String, MeshHandle>,
+    owners: HashMap<Entity, MeshHandle>,
+    next_handle: u32,
+}
+
+impl<A> Default for MeshAssets<A>{
+    fn default() -> Self {
+        MeshAssets{
+            meshes: HashMap::new(),
+            keys: HashMap::new(),
+            owners: HashMap::new(),
+            next_handle: 0,
+        }
+    }
+}
+
+impl MeshAssets{
+    pub fn new() -> Self {
+        MeshAssets::default()
+    }
+
+    // the mesh already loaded under key, or a new one from create. either way the caller holds a reference
+    pub fn load<F>(&mut self, key: &str, queue: Arc<Queue>, create: F) -> MeshHandle
+    where
+        F: FnOnce() -> MeshData,
+    {
+        self.load_with(key, || MeshAsset::upload(create(), queue))
+    }
+
+    // a mesh nothing else can share, like one built at runtime
+    pub fn add(&mut self, mesh: MeshData, queue: Arc<Queue>) -> MeshHandle {
+        self.insert(MeshAsset::upload(mesh, queue), None)
+    }
+}
+
+impl<A> MeshAssets<A>{
+    // load without the upload. create only runs when nothing is loaded under key yet
+    pub fn load_with<F>(&mut self, key: &str, create: F) -> MeshHandle
+    where
+        F: FnOnce() -> A,
+    {
+        if let Some(handle) = self.keys.get(key).cloned() {
+            self.retain(handle);
+            return handle;
+        }
+        let handle = self.insert(create(), Some(key.to_string()));
+        self.keys.insert(key.to_string(), handle);
+        handle
+    }
+
+    pub fn get(&self, handle: MeshHandle) -> Option<&A> {
+        self.meshes.get(&handle).map(|entry| &entry.asset)
+    }
+
+    // what a mesh was loaded as. None for meshes that were added directly or have been freed
+    pub fn key(&self, handle: MeshHandle) -> Option<&str> {
+        self.meshes.get(&handle).and_then(|entry| entry.key.as_deref())
+    }
+
+    pub fn retain(&mut self, handle: MeshHandle){
+        match self.meshes.get_mut(&handle) {
+            Some(entry) => entry.references += 1,
+            None => log::error!("Tried to retain mesh {:?} which has already been freed.", handle),
+        }
+    }
+
+    // true when that was the last reference and the mesh got freed
+    pub fn release(&mut self, handle: MeshHandle) -> bool {
+        let entry = match self.meshes.get_mut(&handle) {
+            Some(entry) => entry,
+            None => {
+                log::error!("Tried to release mesh {:?} which has already been freed.", handle);
+                return false;
+            }
+        };
+        entry.references -= 1;
+        if entry.references > 0 {
+            return false;
+        }
+        if let Some(entry) = self.meshes.remove(&handle) {
+            if let Some(key) = entry.key {
+                self.keys.remove(&key);
+            }
+            log::debug!("Freed mesh {:?}.", handle);
+        }
+        true
+    }
+
+    pub fn references(&self, handle: MeshHandle) -> usize {
+        self.meshes.get(&handle).map_or(0, |entry| entry.references)
+    }
+
+    // hands a reference the caller holds over to an entity. whatever the entity held before is let go
+    pub fn assign(&mut self, entity: Entity, handle: MeshHandle){
+        if let Some(previous) = self.owners.insert(entity, handle) {
+            self.release(previous);
+        }
+    }
+
+    // for entities that were despawned or lost their geometry
+    pub fn unassign(&mut self, entity: Entity){
+        if let Some(handle) = self.owners.remove(&entity) {
+            self.release(handle);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.meshes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meshes.is_empty()
+    }
+
+    fn insert(&mut self, asset: A, key: Option<String>) -> MeshHandle {
+        let handle = MeshHandle(self.next_handle);
+        self.next_handle += 1;
+        self.meshes.insert(handle, MeshEntry{
+            asset: asset,
+            key: key,
+            references: 1,
+        });
+        handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loading_the_same_key_shares_the_mesh() {
+        let mut assets = MeshAssets::<&str>::default();
+        let first = assets.load_with("cube", || "cube");
+        let second = assets.load_with("cube", || panic!("the cube was already loaded"));
+        assert_eq!(first, second);
+        assert_eq!(assets.references(first), 2);
+        assert_eq!(assets.len(), 1);
+
+        let other = assets.load_with("sphere", || "sphere");
+        assert_ne!(first, other);
+        assert_eq!(assets.get(other), Some(&"sphere"));
+        assert_eq!(assets.len(), 2);
+    }
+
+    #[test]
+    fn mesh_is_freed_with_its_last_reference() {
+        let mut assets = MeshAssets::<&str>::default();
+        let handle = assets.load_with("cube", || "cube");
+        assets.retain(handle);
+        assert_eq!(assets.references(handle), 2);
+
+        assert!(!assets.release(handle));
+        assert_eq!(assets.get(handle), Some(&"cube"));
+        assert!(assets.release(handle));
+        assert!(assets.get(handle).is_none());
+        assert_eq!(assets.references(handle), 0);
+        assert!(assets.is_empty());
+
+        // releasing again doesn't underflow or free anything
+        assert!(!assets.release(handle));
+
+        // the key is free again, so the next load gets a fresh mesh
+        let reloaded = assets.load_with("cube", || "new cube");
+        assert_ne!(reloaded, handle);
+        assert_eq!(assets.get(reloaded), Some(&"new cube"));
+    }
+
+    #[test]
+    fn entities_hand_their_reference_back() {
+        let mut assets = MeshAssets::<&str>::default();
+        let (a, b) = (Entity::from_raw(0), Entity::from_raw(1));
+        let cube = assets.load_with("cube", || "cube");
+        assets.assign(a, cube);
+        let shared = assets.load_with("cube", || "cube");
+        assets.assign(b, shared);
+        assert_eq!(assets.references(cube), 2);
+
+        // reassigning lets go of the old mesh
+        let sphere = assets.load_with("sphere", || "sphere");
+        assets.assign(b, sphere);
+        assert_eq!(assets.references(cube), 1);
+
+        assets.unassign(a);
+        assert!(assets.get(cube).is_none());
+        // an entity that was already unassigned has nothing to give back
+        assets.unassign(a);
+        assert_eq!(assets.references(sphere), 1);
+
+        assets.unassign(b);
+        assert!(assets.is_empty());
+    }
+}
@@ -1,5 +1,13 @@
 pub mod geometries;
 pub mod shaders;
 pub mod scene_state;
+pub mod mesh_assets;
+pub mod upload;
 
-pub use scene_state::SceneState;
\ No newline at end of file
+pub use scene_state::SceneState;
+pub use upload::upload_buffer;
+pub use mesh_assets::{
+    MeshAssets,
+    MeshAsset,
+    MeshHandle,
+};
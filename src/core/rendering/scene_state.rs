@@ -1,20 +1,24 @@
 use crate::core::systems::render_systems::DirectionalLightingSystemPipeline;
 use crate::core::systems::render_systems::AmbientLightingSystemPipeline;
+use crate::core::systems::render_systems::PointLightingSystemPipeline;
 use crate::core::systems::render_systems::RenderableDrawSystemPipeline;
 use crate::core::systems::terrain_systems::TerrainDrawSystemPipeline;
 use crate::core::systems::RequiresGraphicsPipeline;
+use crate::core::rendering::geometries::ScreenVertex;
+use crate::core::rendering::upload_buffer;
 
 use vulkano::pipeline::GraphicsPipeline;
 use vulkano::render_pass::RenderPass;
 use vulkano::format::Format;
-use vulkano::swapchain::Swapchain;
 use vulkano::device::Device;
+use vulkano::device::Queue;
+use vulkano::buffer::BufferUsage;
+use vulkano::buffer::ImmutableBuffer;
 use vulkano::image::view::ImageView;
 use vulkano::image::AttachmentImage;
 use vulkano::pipeline::graphics::viewport::Viewport;
 use vulkano::render_pass::Framebuffer;
 use vulkano::render_pass::FramebufferCreateInfo;
-use vulkano::image::SwapchainImage;
 use vulkano::image::ImageUsage;
 use vulkano::image::ImageAccess;
 
@@ -33,6 +37,8 @@ pub struct SceneState{
     pub depth_buffer: Option<Arc<Mutex<Arc<ImageView<AttachmentImage>>>>>,
     pub viewport: Option<Arc<Mutex<Viewport>>>,
     pub framebuffers: Arc<Mutex<Option<Arc<Framebuffer>>>>,
+    // what the lighting passes draw over the whole screen. never changes so it's only uploaded once
+    pub fullscreen_quad: Option<Arc<ImmutableBuffer<[ScreenVertex]>>>,
 }
 
 impl SceneState{
@@ -45,13 +51,17 @@ impl SceneState{
             depth_buffer: None,
             viewport: None,
             framebuffers: Arc::new(Mutex::new(None)),
+            fullscreen_quad: None,
         }
     }
 
+    // final_format is the format of the image we end up drawing into. for windowed rendering that's the
+    // swapchain image format, for headless rendering it's the format of the offscreen attachment image
     pub fn initialize(
         &mut self,
-        swapchain: Arc<Swapchain<winit::window::Window>>,
+        final_format: Format,
         device: Arc<Device>,
+        queue: Arc<Queue>,
     ){  
         // crucially, this does not initialize the framebuffer. to initialize the framebuffer, we must call scale framebuffers to images
 
@@ -60,14 +70,15 @@ impl SceneState{
             diffuse_buffer,
             normals_buffer,
             depth_buffer
-        ) = self.build_buffers(device.clone(), None);
+        ) = self.build_buffers(device.clone(), [1, 1]);
     
         // create pass
-        let pass = self.build_render_pass(swapchain.image_format(), device.clone());
+        let pass = self.build_render_pass(final_format, device.clone());
 
         // create pipelines
         let directional_lighting_pipeline = DirectionalLightingSystemPipeline::create_graphics_pipeline(device.clone(), pass.clone());
         let ambient_lighting_pipeline = AmbientLightingSystemPipeline::create_graphics_pipeline(device.clone(), pass.clone());
+        let point_lighting_pipeline = PointLightingSystemPipeline::create_graphics_pipeline(device.clone(), pass.clone());
         let renderable_pipeline = RenderableDrawSystemPipeline::create_graphics_pipeline(device.clone(), pass.clone());
         let terrain_draw_pipeline = TerrainDrawSystemPipeline::create_graphics_pipeline(device.clone(), pass.clone());
         
@@ -86,6 +97,7 @@ impl SceneState{
         pipelines.insert(TypeId::of::<DirectionalLightingSystemPipeline>(), directional_lighting_pipeline);
         pipelines.insert(TypeId::of::<RenderableDrawSystemPipeline>(), renderable_pipeline);
         pipelines.insert(TypeId::of::<AmbientLightingSystemPipeline>(), ambient_lighting_pipeline);
+        pipelines.insert(TypeId::of::<PointLightingSystemPipeline>(), point_lighting_pipeline);
         pipelines.insert(TypeId::of::<TerrainDrawSystemPipeline>(), terrain_draw_pipeline);
         
         // add buffers
@@ -95,17 +107,20 @@ impl SceneState{
 
         // add viewport
         self.viewport = Some(Arc::new(Mutex::new(viewport)));
+
+        // add fullscreen quad
+        self.fullscreen_quad = Some(upload_buffer(queue, BufferUsage::vertex_buffer(), ScreenVertex::fullscreen_quad()));
     }
 
-    fn build_render_pass(&self, swapchain_format: Format, device: Arc<Device>) -> Arc<RenderPass> {
+    fn build_render_pass(&self, final_format: Format, device: Arc<Device>) -> Arc<RenderPass> {
         let render_pass = vulkano::ordered_passes_renderpass!(device.clone(),
                 attachments: {
-                    // The image that will contain the final rendering (in this example the swapchain
-                    // image, but it could be another image).
+                    // The image that will contain the final rendering (the swapchain image, or the
+                    // offscreen attachment image when running headless).
                     final_color: {
                         load: Clear,
                         store: Store,
-                        format: swapchain_format,
+                        format: final_format,
                         samples: 1,
                     },
                     // Will be bound to `self.diffuse_buffer`.
@@ -155,17 +170,11 @@ impl SceneState{
         render_pass
     }
 
-    fn build_buffers(&self, device: Arc<Device>, image: Option<Arc<ImageView<SwapchainImage<winit::window::Window>>>>)
+    fn build_buffers(&self, device: Arc<Device>, image_dim: [u32; 2])
     -> (Arc<ImageView<AttachmentImage>>, Arc<ImageView<AttachmentImage>>, Arc<ImageView<AttachmentImage>>){
         // For now we create three temporary images with a dimension of 1 by 1 pixel.
         // These images will be replaced the first time we call `frame()`.
         // TODO: use shortcut provided in vulkano 0.6
-        let image_dim = {
-            match image{
-                Some(image) => image.image().dimensions().width_height(),
-                None => [1, 1]
-            }
-        };
         let atch_usage = ImageUsage {
             transient_attachment: true,
             input_attachment: true,
@@ -204,12 +213,19 @@ impl SceneState{
         (diffuse_buffer, normals_buffer, depth_buffer)
     }
 
-    pub fn scale_scene_state_to_images(&self, image: Arc<ImageView<SwapchainImage<winit::window::Window>>>, device: Arc<Device>){
+    // works for both swapchain images and the offscreen attachment image used in headless mode
+    pub fn scale_scene_state_to_images<I>(&self, image: Arc<ImageView<I>>, device: Arc<Device>)
+    where
+        I: ImageAccess + 'static,
+    {
         self.scale_framebuffers_to_images(image.clone(), device.clone());
         self.rescale_viewport(image.clone());
     }
 
-    fn rescale_viewport(&self, image: Arc<ImageView<SwapchainImage<winit::window::Window>>>){
+    fn rescale_viewport<I>(&self, image: Arc<ImageView<I>>)
+    where
+        I: ImageAccess + 'static,
+    {
         let dimensions = image.image().dimensions().width_height();
         match &self.viewport{
             Some(viewport) => viewport.lock().unwrap().dimensions = [dimensions[0] as f32, dimensions[1] as f32],
@@ -217,15 +233,18 @@ impl SceneState{
         }
     }
 
-    fn scale_framebuffers_to_images(&self, image: Arc<ImageView<SwapchainImage<winit::window::Window>>>, device: Arc<Device>){
+    fn scale_framebuffers_to_images<I>(&self, image: Arc<ImageView<I>>, device: Arc<Device>)
+    where
+        I: ImageAccess + 'static,
+    {
+        let dimensions = image.clone().image().dimensions().width_height();
+
         // create buffers
         let (
             _diffuse_buffer,
             _normals_buffer,
             _depth_buffer
-        ) = self.build_buffers(device.clone(), Some(image.clone()));
-
-        let dimensions = image.clone().image().dimensions().width_height();
+        ) = self.build_buffers(device.clone(), dimensions);
 
         let atch_usage = ImageUsage {
             transient_attachment: true,
@@ -307,6 +326,10 @@ impl SceneState{
         self.normals_buffer.clone().unwrap().lock().unwrap().clone()
     }
 
+    pub fn fullscreen_quad(&self) -> Arc<ImmutableBuffer<[ScreenVertex]>> {
+        self.fullscreen_quad.clone().expect("Scene state hasn't been initialized, there's no fullscreen quad.")
+    }
+
     pub fn depth_buffer(&self) -> Arc<ImageView<AttachmentImage>> {
         self.depth_buffer.clone().unwrap().lock().unwrap().clone()
     }
@@ -23,9 +23,9 @@ vulkano_shaders::shader! {
         vec3 in_normal = normalize(subpassLoad(u_normals).rgb);
     
         // If the normal is perpendicular to the direction of the lighting, then `light_percent` will
-        // be 0. If the normal is parallel to the direction of the lightin, then `light_percent` will
+        // be 0. If the normal faces straight back at the light, then `light_percent` will
         // be 1. Any other angle will yield an intermediate value.
-        float light_percent = dot(push_constants.direction.xyz, in_normal);
+        float light_percent = -dot(normalize(push_constants.direction.xyz), in_normal);
         
         // `light_percent` must not go below 0.0. There's no such thing as negative lighting.
         
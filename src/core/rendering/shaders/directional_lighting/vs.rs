@@ -2,8 +2,8 @@ vulkano_shaders::shader! {
     ty: "vertex",
     src: "
     #version 450
-    layout(location = 0) in vec3 position;
+    layout(location = 0) in vec2 position;
     void main() {
-        gl_Position = vec4(position, 1.0);
+        gl_Position = vec4(position, 0.0, 1.0);
     }"
 }
\ No newline at end of file
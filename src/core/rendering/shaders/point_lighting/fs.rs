@@ -13,18 +13,23 @@ vulkano_shaders::shader! {
         mat4 screen_to_world;
         // The `color` parameter of the `draw` method.
         vec4 color;
-        // The `position` parameter of the `draw` method.
+        // The `position` parameter of the `draw` method. w is the light's range.
         vec4 position;
+        // x and y are the slice of the depth buffer the camera being lit drew into.
+        vec4 depth_range;
     } push_constants;
     layout(location = 0) in vec2 v_screen_coords;
     layout(location = 0) out vec4 f_color;
     void main() {
         float in_depth = subpassLoad(u_depth).x;
         // Any depth superior or equal to 1.0 means that the pixel has been untouched by the deferred
-        // pass. We don't want to deal with them.
-        if (in_depth >= 1.0) {
+        // pass. We don't want to deal with them. Pixels outside this camera's slice of the depth
+        // range were drawn by another camera.
+        if (in_depth >= 1.0 || in_depth < push_constants.depth_range.x || in_depth >= push_constants.depth_range.y) {
             discard;
         }
+        // Back to the depth the camera's projection produced.
+        in_depth = (in_depth - push_constants.depth_range.x) / (push_constants.depth_range.y - push_constants.depth_range.x);
         // Find the world coordinates of the current pixel.
         vec4 world = push_constants.screen_to_world * vec4(v_screen_coords, in_depth, 1.0);
         world /= world.w;
@@ -33,11 +38,18 @@ vulkano_shaders::shader! {
         vec3 light_direction = normalize(push_constants.position.xyz - world.xyz);
         // Calculate the percent of lighting that is received based on the orientation of the normal
         // and the direction of the light.
-        float light_percent = max(-dot(light_direction, in_normal), 0.0);
+        float light_percent = max(dot(light_direction, in_normal), 0.0);
 
         float light_distance = length(push_constants.position.xyz - world.xyz);
-        // Further decrease light_percent based on the distance with the light position.
-        light_percent *= 1.0 / exp(light_distance);
+        float light_range = push_constants.position.w;
+        // Nothing past the range gets lit.
+        if (light_distance >= light_range) {
+            discard;
+        }
+        // Further decrease light_percent based on the distance with the light position, falling off
+        // smoothly to 0 at the edge of the range.
+        float falloff = 1.0 - light_distance / light_range;
+        light_percent *= falloff * falloff;
 
         vec3 in_diffuse = subpassLoad(u_diffuse).rgb;
         f_color.rgb = push_constants.color.rgb * light_percent * in_diffuse;
@@ -3,24 +3,20 @@ vulkano_shaders::shader! {
     src: "
         #version 450
         layout(location = 0) in vec3 in_pos;
+        layout(location = 1) in vec3 in_normal;
+        layout(location = 2) in vec4 in_color;
 
         layout(location = 0) out vec4 f_color;
         layout(location = 1) out vec3 f_normal;
 
-        vec3 calculateScreenSpaceNormal(vec3 p) {
-            vec3 dx = dFdx(p);
-            vec3 dy = -dFdy(p); // not sure if negation is needed for Vulkan
-            return normalize(cross(dx, dy));
-        }
-        
         void main() {
             if (in_pos.z > 1.0) {
-                f_color = vec4(0.8, 0.4, 0.4, 1.0);
+                f_color = vec4(0.8, 0.4, 0.4, 1.0) * in_color;
             }else{
-                f_color = vec4(0.5, 0.2, 0.2, 1.0);
+                f_color = vec4(0.5, 0.2, 0.2, 1.0) * in_color;
             }
-            // f_normal = vec3(1.0, 1.0, 1.0);
-            f_normal = calculateScreenSpaceNormal(in_pos);
+            // world space, facing out of the surface
+            f_normal = normalize(in_normal);
         }
 
     "
@@ -8,14 +8,22 @@ vulkano_shaders::shader! {
     src: "
         #version 450
         layout(location = 0) in vec3 position;
+        layout(location = 1) in vec3 normal;
+        layout(location = 2) in vec4 color;
         layout(location = 0) out vec3 outPos;
+        layout(location = 1) out vec3 outNormal;
+        layout(location = 2) out vec4 outColor;
 
         layout(set = 0, binding = 0) uniform Data {
             mat4 mwv;
+            // inverse transpose of the model matrix, keeps normals square to scaled surfaces
+            mat4 normal_matrix;
         } uniforms;
 
         void main() {
             outPos = position;
+            outNormal = mat3(uniforms.normal_matrix) * normal;
+            outColor = color;
             gl_Position = uniforms.mwv * vec4(position, 1.0);
         }
     "
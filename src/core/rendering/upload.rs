@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::BufferContents,
+    buffer::BufferUsage,
+    buffer::ImmutableBuffer,
+    device::Queue,
+    sync::GpuFuture,
+};
+
+// copies data into a device local buffer through a staging buffer, and waits for the copy to finish so the
+// buffer can be drawn from straight away. for data that's written once and never touched again
+pub fn upload_buffer<T, D>(queue: Arc<Queue>, usage: BufferUsage, data: D) -> Arc<ImmutableBuffer<[T]>>
+where
+    D: IntoIterator<Item = T>,
+    D::IntoIter: ExactSizeIterator,
+    [T]: BufferContents,
+{
+    let (buffer, upload) = ImmutableBuffer::from_iter(data, usage, queue)
+        .expect("Couldn't allocate device local buffer.");
+    upload
+        .then_signal_fence_and_flush()
+        .expect("Couldn't submit buffer upload.")
+        .wait(None)
+        .expect("Buffer upload didn't finish.");
+    buffer
+}
@@ -0,0 +1,229 @@
+use bevy_ecs::{
+    component::Component,
+    entity::{
+        Entity,
+        EntityMap,
+        MapEntities,
+        MapEntitiesError,
+    },
+    world::World,
+};
+use serde::{
+    Serialize,
+    Deserialize,
+    de::DeserializeOwned,
+};
+use ron::ser::PrettyConfig;
+
+use std::any::type_name;
+use std::collections::BTreeMap;
+
+use crate::core::plugins::components::{
+    CameraComponent,
+    FlyCameraController,
+    OrbitCameraController,
+    PanZoomCameraController,
+    GeometryComponent,
+    InputComponent,
+    RenderableComponent,
+    TransformComponent,
+    TransformUiComponent,
+    Parent,
+    Children,
+    VelocityComponent,
+};
+
+// serializes a single component type off of an entity. None if the entity doesn't have one
+type SerializeFn = fn(&World, Entity) -> Option<ron::Result<String>>;
+// deserializes a single component type and inserts it onto an entity
+type DeserializeFn = fn(&str, &mut World, Entity) -> ron::Result<()>;
+// points entity references held by a component at the newly spawned entities after a load
+type MapEntitiesFn = fn(&mut World, Entity, &EntityMap) -> Result<(), MapEntitiesError>;
+
+#[derive(Clone)]
+pub struct ComponentRegistration{
+    pub name: &'static str,
+    serialize: SerializeFn,
+    deserialize: DeserializeFn,
+    map_entities: Option<MapEntitiesFn>,
+}
+
+// what a scene looks like on disk. each component is stored as its own ron string keyed by type name
+#[derive(Serialize, Deserialize, Default)]
+pub struct SerializedScene{
+    pub entities: Vec<SerializedEntity>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedEntity{
+    pub entity: u32,
+    pub components: BTreeMap<String, String>,
+}
+
+// lists which components get written out when a scene is saved and how to read them back in
+#[derive(Clone, Default)]
+pub struct ComponentRegistry{
+    registrations: Vec<ComponentRegistration>,
+}
+
+impl ComponentRegistry{
+    pub fn new() -> Self {
+        ComponentRegistry{
+            registrations: Vec::new(),
+        }
+    }
+
+    // registry with the serializable components the engine core uses. plugins register their own
+    pub fn with_engine_components() -> Self {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<TransformComponent>();
+        registry.register::<TransformUiComponent>();
+        registry.register_with_entity_map::<Parent>();
+        registry.register_with_entity_map::<Children>();
+        registry.register::<VelocityComponent>();
+        registry.register::<CameraComponent>();
+        registry.register::<FlyCameraController>();
+        registry.register::<OrbitCameraController>();
+        registry.register::<PanZoomCameraController>();
+        registry.register::<InputComponent>();
+        registry.register::<RenderableComponent>();
+        registry.register::<GeometryComponent>();
+        registry
+    }
+
+    pub fn register<T>(&mut self)
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        self.add_registration(ComponentRegistration{
+            name: ComponentRegistry::component_name::<T>(),
+            serialize: serialize_component::<T>,
+            deserialize: deserialize_component::<T>,
+            map_entities: None,
+        });
+    }
+
+    // for components that hold on to other entities
+    pub fn register_with_entity_map<T>(&mut self)
+    where
+        T: Component + Serialize + DeserializeOwned + MapEntities,
+    {
+        self.add_registration(ComponentRegistration{
+            name: ComponentRegistry::component_name::<T>(),
+            serialize: serialize_component::<T>,
+            deserialize: deserialize_component::<T>,
+            map_entities: Some(map_component_entities::<T>),
+        });
+    }
+
+    pub fn is_registered<T: Component>(&self) -> bool {
+        self.get(ComponentRegistry::component_name::<T>()).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ComponentRegistration> {
+        self.registrations.iter().find(|registration| registration.name == name)
+    }
+
+    // writes every entity with at least one registered component out to a ron string
+    pub fn serialize_world(&self, world: &mut World) -> ron::Result<String> {
+        let mut entities = world.query::<Entity>().iter(world).collect::<Vec<Entity>>();
+        entities.sort_by_key(|entity| entity.id());
+
+        let mut scene = SerializedScene::default();
+        for entity in entities {
+            let mut components = BTreeMap::new();
+            for registration in self.registrations.iter() {
+                if let Some(data) = (registration.serialize)(world, entity) {
+                    components.insert(registration.name.to_string(), data?);
+                }
+            }
+            if !components.is_empty() {
+                scene.entities.push(SerializedEntity{
+                    entity: entity.id(),
+                    components: components,
+                });
+            }
+        }
+
+        let pretty = PrettyConfig::new()
+            .depth_limit(3)
+            .separate_tuple_members(true);
+        ron::ser::to_string_pretty(&scene, pretty)
+    }
+
+    // spawns the entities in a serialized scene into the world. entity references are remapped onto the
+    // new entities. components that fail to load or aren't registered are logged and skipped.
+    pub fn deserialize_world(&self, world: &mut World, data: &str) -> ron::Result<()> {
+        let scene: SerializedScene = ron::from_str(data)?;
+
+        // spawn everything first so references between entities can be resolved
+        let mut entity_map = EntityMap::default();
+        for serialized in scene.entities.iter() {
+            let entity = world.spawn().id();
+            entity_map.insert(Entity::from_raw(serialized.entity), entity);
+        }
+
+        for serialized in scene.entities.iter() {
+            let entity = entity_map.get(Entity::from_raw(serialized.entity)).unwrap();
+            for (name, component) in serialized.components.iter() {
+                match self.get(name) {
+                    Some(registration) => {
+                        if let Err(e) = (registration.deserialize)(component, world, entity) {
+                            log::error!("Couldn't deserialize {} on entity {}: {}", name, serialized.entity, e);
+                        }
+                    },
+                    None => log::warn!("Component {} isn't registered, skipping it.", name),
+                }
+            }
+        }
+
+        for registration in self.registrations.iter() {
+            if let Some(map_entities) = registration.map_entities {
+                for entity in entity_map.values() {
+                    if let Err(e) = map_entities(world, entity, &entity_map) {
+                        log::error!("Couldn't map entities for {}: {}", registration.name, e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add_registration(&mut self, registration: ComponentRegistration){
+        match self.registrations.iter().position(|r| r.name == registration.name) {
+            Some(i) => self.registrations[i] = registration,
+            None => self.registrations.push(registration),
+        }
+    }
+
+    // type name without the module path, this is what shows up in scene files
+    fn component_name<T>() -> &'static str {
+        type_name::<T>().rsplit("::").next().unwrap()
+    }
+}
+
+fn serialize_component<T>(world: &World, entity: Entity) -> Option<ron::Result<String>>
+where
+    T: Component + Serialize,
+{
+    world.get::<T>(entity).map(|component| ron::to_string(component))
+}
+
+fn deserialize_component<T>(data: &str, world: &mut World, entity: Entity) -> ron::Result<()>
+where
+    T: Component + DeserializeOwned,
+{
+    let component: T = ron::from_str(data)?;
+    world.entity_mut(entity).insert(component);
+    Ok(())
+}
+
+fn map_component_entities<T>(world: &mut World, entity: Entity, entity_map: &EntityMap) -> Result<(), MapEntitiesError>
+where
+    T: Component + MapEntities,
+{
+    match world.get_mut::<T>(entity) {
+        Some(mut component) => component.map_entities(entity_map),
+        None => Ok(()),
+    }
+}
@@ -1,7 +1,10 @@
 pub mod scene;
 pub mod scene_macros;
+pub mod component_registry;
 
 pub use scene::Scene;
 pub use scene::Active;
 pub use scene::Staged;
 pub use scene::Inactive;
+pub use scene::SceneInterfacePath;
+pub use component_registry::ComponentRegistry;
@@ -2,6 +2,7 @@ use bevy_ecs::{
     prelude::Schedule,
     prelude::SystemStage,
     world::World,
+    world::Mut,
     schedule::Stage,
     system::Resource,
 };
@@ -15,40 +16,53 @@ use std::{
     },
 };
 use std::borrow::BorrowMut;
+use std::fs;
 
 use crate::core::managers::input_manager::KeyInputQueue;
+use crate::core::scene::component_registry::ComponentRegistry;
+use crate::core::plugins::{
+    Plugin,
+    stages,
+};
 use crate::core::systems::{
-    ui_systems::{
-        DebugUiSystem,
-    },
     input_systems::{
-        CameraMoveSystem,
+        FlyCameraControllerSystem,
+        OrbitCameraControllerSystem,
+        PanZoomCameraControllerSystem,
+        MouseDeltaResetSystem,
+        ActionTriggerResetSystem,
     },
     render_systems::{
         RenderableInitializerSystem,
         RenderableDrawSystem,
         CameraUpdateSystem,
-        DirectionalLightingSystem,
-        AmbientLightingSystem,
         RenderableAssemblyStateModifierSystem,
     },
-    ui_systems::{
-        CameraUiSystem,
+    picking_systems::{
+        PickingSystem,
+        PickResult,
     },
     CameraInitSystem,
-    TerrainInitSystem,
-    TerrainDrawSystem,
-    TerrainAssemblyStateModifierSystem,
-    TerrainUiSystem,
     GeometryInitializerSystem,
+    MeshAssetCleanupSystem,
+    TransformSnapshotSystem,
+    TransformPropagationSystem,
+    ParentSyncSystem,
+    KinematicsSystem,
 };
 
 
 
 
+// where a scene was loaded from. saving writes back to this file
+pub struct SceneInterfacePath(pub &'static str);
+
 pub struct Scene<S>{
     pub world: Option<RefCell<World>>,
     pub state: S,
+    // set once the world has been prepped and set up. a scene that's set aside and staged again keeps its
+    // resources and uploads, so none of that runs a second time
+    pub activated: bool,
 }
 
 pub struct Active{
@@ -67,6 +81,7 @@ impl Scene<Inactive> {
         Scene{
             world: None,
             state: Inactive,
+            activated: false,
         }
     }
 }
@@ -77,15 +92,24 @@ impl Scene<Staged> {
         let mut schedule = Schedule::default();
         log::info!("Creating setup schedule.");
         schedule
-        .add_stage("geometry_init", SystemStage::parallel()
+        .add_stage(stages::GEOMETRY_INIT, SystemStage::parallel()
             .with_system(GeometryInitializerSystem)
-            .with_system(TerrainInitSystem)
-        ).add_stage("final_init", SystemStage::parallel()
+        ).add_stage(stages::FINAL_INIT, SystemStage::parallel()
             .with_system(CameraInitSystem)
             .with_system(RenderableInitializerSystem)
         );
         self.state.setup_schedule = Some(schedule);
     }
+
+    // adds plugin resources and setup systems. must happen before the scene is activated, since that's
+    // when the setup schedule runs
+    pub fn apply_plugins(&mut self, plugins: &Vec<Box<dyn Plugin>>){
+        for plugin in plugins.iter() {
+            log::debug!("Applying plugin {} to staged scene.", plugin.name());
+            plugin.insert_resources(self);
+            plugin.build_setup_schedule(self.state.setup_schedule.as_mut().expect("No setup schedule"));
+        }
+    }
     
     fn create_teardown_schedule(&mut self){
         let schedule = Schedule::default();
@@ -190,35 +214,58 @@ impl Scene<Active> {
         let mut schedule = Schedule::default();
         
         schedule
-        .add_stage("camera_move", SystemStage::parallel()
-            .with_system(CameraMoveSystem)
-        ).add_stage_after("camera_move", "camera_update", SystemStage::parallel()
+        .add_stage(stages::CAMERA_MOVE, SystemStage::parallel()
+            .with_system(FlyCameraControllerSystem)
+            .with_system(OrbitCameraControllerSystem)
+            .with_system(PanZoomCameraControllerSystem)
+            .with_system(ParentSyncSystem)
+        ).add_stage_after(stages::CAMERA_MOVE, stages::PROPAGATE, SystemStage::parallel()
+            .with_system(TransformPropagationSystem)
+            .with_system(GeometryInitializerSystem)
+        ).add_stage_after(stages::PROPAGATE, stages::CAMERA_UPDATE, SystemStage::parallel()
             .with_system(CameraUpdateSystem)
-        ).add_stage("wireframe_input_system", SystemStage::parallel()
-            .with_system(RenderableAssemblyStateModifierSystem)
-        ).add_stage("assembly_state_modifier_system", SystemStage::parallel()
-            .with_system(TerrainAssemblyStateModifierSystem)
-        ).add_stage_after("camera_update", "main", SystemStage::parallel()
+            .with_system(MouseDeltaResetSystem)
+        ).add_stage_after(stages::CAMERA_UPDATE, stages::MAIN, SystemStage::parallel()
             .with_system(RenderableDrawSystem)
-            .with_system(DirectionalLightingSystem)
-            .with_system(AmbientLightingSystem)
-            .with_system(TerrainDrawSystem)
-        ).add_stage("ui_stage", SystemStage::single_threaded()
-            .with_system(TerrainUiSystem)
-            .with_system(DebugUiSystem)
-            .with_system(CameraUiSystem)
+            .with_system(PickingSystem)
+        ).add_stage_after(stages::MAIN, stages::INPUT, SystemStage::parallel()
+            .with_system(RenderableAssemblyStateModifierSystem)
+        ).add_stage_after(stages::INPUT, stages::ASSEMBLY_STATE, SystemStage::parallel()
+        ).add_stage_after(stages::ASSEMBLY_STATE, stages::UI, SystemStage::single_threaded()
+        ).add_stage_after(stages::UI, stages::CLEANUP, SystemStage::parallel()
+            .with_system(MeshAssetCleanupSystem)
+            .with_system(ActionTriggerResetSystem)
         );
         self.state.render_schedule = Some(schedule);
     }
 
     pub fn create_update_schedule(&mut self){
-        let schedule = Schedule::default();
+        let mut schedule = Schedule::default();
+        schedule
+        .add_stage(stages::PRE_UPDATE, SystemStage::parallel()
+            .with_system(TransformSnapshotSystem)
+        ).add_stage_after(stages::PRE_UPDATE, stages::UPDATE, SystemStage::parallel()
+            .with_system(KinematicsSystem)
+        ).add_stage_after(stages::UPDATE, stages::PHYSICS, SystemStage::parallel());
         self.state.update_schedule = Some(schedule);
     }
 
+    // adds plugin systems to the update and render schedules
+    pub fn apply_plugins(&mut self, plugins: &Vec<Box<dyn Plugin>>){
+        for plugin in plugins.iter() {
+            log::debug!("Applying plugin {} to active scene.", plugin.name());
+            plugin.build_update_schedule(self.state.update_schedule.as_mut().expect("No update schedule"));
+            plugin.build_render_schedule(self.state.render_schedule.as_mut().expect("No render schedule"));
+        }
+    }
+
     pub fn run_render_schedule(&mut self){
         let mut schedule = self.state.render_schedule.take().unwrap();
-        schedule.run(&mut *self.get_world().unwrap());
+        let mut world = self.get_world().unwrap();
+        schedule.run(&mut *world);
+        // removals have been seen by cleanup, don't report them again next frame
+        world.clear_trackers();
+        drop(world);
         self.state.render_schedule = Some(schedule);
     }
 
@@ -230,84 +277,84 @@ impl Scene<Active> {
 
     pub fn insert_required_resources(&mut self){
         self.insert_resource(KeyInputQueue::new());
+        self.insert_resource(PickResult::default());
     }
 }
 
-impl <Active> Scene<Active>{
+impl Scene<Active>{
+    // writes all registered components in the world to the scene's interface file
     pub fn serialize(&mut self){
         log::info!("Serializing Scene");
-        // let mut worldref = self.world.take().unwrap();
-        // let world = worldref.get_mut();
-        
-        // // Actually serialize
-        // {
-        //     let data = ( world.entities(), world.read_storage::<SimpleMarker<SerializerFlag>>() );
-
-        //     let pretty = PrettyConfig::new()
-        //         .depth_limit(2)
-        //         .separate_tuple_members(true)
-        //         .enumerate_arrays(true);
-
-        //     let writer = File::create("./savegame.ron").unwrap();
-        //     let mut serializer = ron::ser::Serializer::new(writer, Some(pretty), true).expect("Couldn't create ron serializer.");
-        //     serialize_individually!(
-        //         world,
-        //         serializer,
-        //         data,
-        //         InputComponent,
-        //         CameraComponent,
-        //         TransformComponent,
-        //         TransformUiComponent,
-        //         DebugUiComponent,
-        //         RenderableComponent,
-        //         DirectionalLightComponent,
-        //         AmbientLightingComponent,
-        //         TerrainComponent,
-        //         TerrainUiComponent,
-        //         GeometryComponent
-        //     );
-        // }
-
-        // self.world = Some(worldref);
+        let mut world = self.get_world().expect("No world to serialize.");
 
+        let path = match world.get_resource::<SceneInterfacePath>() {
+            Some(path) => path.0,
+            None => "./savegame.ron",
+        };
+
+        if !world.contains_resource::<ComponentRegistry>() {
+            log::error!("No component registry in scene, can't serialize.");
+            return;
+        }
+        let serialized = world.resource_scope(|world, registry: Mut<ComponentRegistry>| {
+            registry.serialize_world(world)
+        });
+
+        match serialized {
+            Ok(data) => match fs::write(path, data) {
+                Ok(()) => log::info!("Scene saved to {}.", path),
+                Err(e) => log::error!("Couldn't write scene to {}: {}", path, e),
+            },
+            Err(e) => log::error!("Couldn't serialize scene: {}", e),
+        }
     }
 }
 
 impl From<Scene<Staged>> for Scene<Active> {
     fn from(mut staged_scene: Scene<Staged>) -> Scene<Active> {
-        staged_scene.run_setup_schedule();
+        let activated = staged_scene.activated;
+        if !activated {
+            staged_scene.run_setup_schedule();
+        }
         let mut scene = Scene{
             world: staged_scene.world,
             state: Active{
                 device_loaded: false,
                 update_schedule: None,
                 render_schedule: None,
-            }
+            },
+            activated: true,
         };
         scene.create_render_schedule();
         scene.create_update_schedule();
-        scene.insert_required_resources();
+        if !activated {
+            scene.insert_required_resources();
+        }
         scene
     }
 }
 
+// the world is kept, so a scene that's set aside can be staged again as it was left
 impl From<Scene<Active>> for Scene<Inactive> {
-    fn from(_active_scene: Scene<Active>) -> Scene<Inactive> {
+    fn from(active_scene: Scene<Active>) -> Scene<Inactive> {
         Scene{
-            world: None,
+            world: active_scene.world,
             state: Inactive,
+            activated: active_scene.activated,
         }
     }
 }
 
 impl From<Scene<Inactive>> for Scene<Staged> {
-    fn from(_inactive_scene: Scene<Inactive>) -> Scene<Staged> {
+    fn from(inactive_scene: Scene<Inactive>) -> Scene<Staged> {
+        let world = inactive_scene.world.unwrap_or_else(|| RefCell::new(World::new()));
         let mut scene = Scene{
-            world: Some(RefCell::new(World::new())),
+            world: Some(world),
             state: Staged{
                 setup_schedule: None,
                 teardown_schedule: None,
             },
+            activated: inactive_scene.activated,
         };
         scene.create_setup_schedule();
         scene.create_teardown_schedule();
@@ -1,99 +1,83 @@
 use bevy_ecs::{
     prelude::{
-        Query, 
+        Changed,
+        Entity,
+        Query,
         Res,
+        ResMut,
+        RemovedComponents,
     }
 };
-use vulkano::device::Device;
+use vulkano::device::Queue;
 
 use std::sync::Arc;
-use crate::core::rendering::geometries::geometry_primitives::{
-    Vertex,
-};
+use crate::core::rendering::geometries::MeshData;
+use crate::core::rendering::MeshAssets;
+use cgmath::Vector3;
 use crate::core::plugins::components::geometry_component::{GeometryComponent, GeometryType};
 
 pub struct GeometryInitHelper;
 
 impl GeometryInitHelper{
-    fn create_geometry(mut geom: &mut GeometryComponent, device: Arc<Device>){
-        match geom.geometry_type{
-            GeometryType::Box => GeometryInitHelper::init_cube(&mut geom),
-            GeometryType::Triangle => GeometryInitHelper::init_triangle(&mut geom),
-            GeometryType::Plane => GeometryInitHelper::init_plane(&mut geom),
-        };
-        geom.initialize(device.clone());
-    }
-
-    fn init_cube(mut geom: &mut GeometryComponent){
-        // dx here is just delta, not associated with x axis
-        let dx = 0.5;
-
-        // bottom plane
-        let tl0 = Vertex::new(0.0 - dx, 0.0 + dx, 0.0 - dx);
-        let tr0 = Vertex::new(0.0 + dx, 0.0 + dx, 0.0 - dx);
-        let bl0 = Vertex::new(0.0 - dx, 0.0 - dx, 0.0 - dx);
-        let br0 = Vertex::new(0.0 + dx, 0.0 - dx, 0.0 - dx);
-
-        // top plane
-        let tl1 = Vertex::new(0.0 - dx, 0.0 + dx, 0.0 + dx);
-        let tr1 = Vertex::new(0.0 + dx, 0.0 + dx, 0.0 + dx);
-        let bl1 = Vertex::new(0.0 - dx, 0.0 - dx, 0.0 + dx);
-        let br1 = Vertex::new(0.0 + dx, 0.0 - dx, 0.0 + dx);
-
-        // store verts.       0    1    2    3    4    5    6    7
-        let vertices = vec![tl0, tr0, bl0, br0, tl1, tr1, bl1, br1];
-
-        // top, front, right, back, left, bottom
-        let indices = vec![
-            4, 5, 7, 6, 4, 7, // top
-            3, 2, 7, 2, 6, 7, // front
-            7, 5, 1, 3, 7, 1, // right
-            5, 4, 0, 1, 5, 0, // back
-            4, 6, 2, 0, 4, 2, // left
-            2, 3, 0, 1, 2, 0, // bottom
-        ];
-
-        geom.vertices = vertices;
-        geom.indices = indices;
-        // geom.initialized = true;
+    fn create_mesh(geometry_type: &GeometryType) -> MeshData {
+        match geometry_type.clone(){
+            GeometryType::Box => GeometryInitHelper::unit_cube(),
+            GeometryType::Triangle => MeshData::triangle(1.0),
+            GeometryType::Plane => MeshData::plane(1.0, 1),
+            GeometryType::SubdividedPlane{size, subdivisions} => MeshData::plane(size, subdivisions),
+            GeometryType::Sphere{radius, segments, rings} => MeshData::sphere(radius, segments, rings),
+            GeometryType::Cylinder{radius, height, segments} => MeshData::cylinder(radius, height, segments),
+            GeometryType::Cone{radius, height, segments} => MeshData::cone(radius, height, segments),
+            GeometryType::Torus{radius, tube_radius, segments, sides} => MeshData::torus(radius, tube_radius, segments, sides),
+            GeometryType::Capsule{radius, height, segments, rings} => MeshData::capsule(radius, height, segments, rings),
+            // falls back to a cube so there's still something to see when the file can't be used
+            GeometryType::Mesh(path) => match MeshData::load(&path) {
+                Ok(mesh) => mesh,
+                Err(e) => {
+                    log::error!("Couldn't load mesh {}: {}", path, e);
+                    GeometryInitHelper::unit_cube()
+                }
+            },
+        }
     }
 
-    fn init_plane(mut geom: &mut GeometryComponent){
-        let corner_offset = 0.5;
-
-        // top left, top right, bottom left, bottom right
-        let tl = Vertex{position: [-corner_offset, corner_offset, 0.0]};
-        let tr = Vertex{position: [corner_offset, corner_offset, 0.0]};
-        let bl = Vertex{position: [-corner_offset, -corner_offset, 0.0]};
-        let br = Vertex{position: [corner_offset, -corner_offset, 0.0]};
-
-        geom.vertices = vec![tl, tr, bl, br];
-        geom.indices = vec![0, 1, 3, 2, 0, 3];
-        // geom.initialized = true;
-    }
-
-    fn init_triangle(mut geom: &mut GeometryComponent){
-        let corner_offset = 0.5;
-        let vertices = vec![
-            Vertex{position: [-corner_offset, -corner_offset, 0.0]},
-            Vertex{position: [0.0, corner_offset, 0.0]},
-            Vertex{position: [corner_offset, -corner_offset, 0.0]}
-        ];
-        geom.vertices = vertices;
-        geom.indices = vec![0, 1, 2, 0];
-        // geom.initialized = true;
+    fn unit_cube() -> MeshData {
+        MeshData::cuboid(Vector3::new(0.5, 0.5, 0.5))
     }
 }
 
 
+// points geometries at their meshes, building and uploading each distinct one once. runs at setup and every
+// frame after, so geometries spawned at runtime or switched to another type get picked up too. switching lets
+// go of the old mesh
 pub fn GeometryInitializerSystem(
-    mut query: Query<&mut GeometryComponent>,
-    device: Res<Arc<Device>>,
+    mut query: Query<(Entity, &mut GeometryComponent), Changed<GeometryComponent>>,
+    mut mesh_assets: ResMut<MeshAssets>,
+    queue: Res<Arc<Queue>>,
 )
 {
     log::debug!("Running geometry init system...");
-    for mut geometry in query.iter_mut() {
-        GeometryInitHelper::create_geometry(&mut geometry, device.clone());
-        geometry.initialize(device.clone());
+    for (entity, mut geometry) in query.iter_mut() {
+        let key = geometry.geometry_type.asset_key();
+        // handing out the mesh below counts as a change, so it comes back through here once more
+        let current = geometry.mesh.and_then(|handle| mesh_assets.key(handle));
+        if current == Some(key.as_str()) {
+            continue;
+        }
+        let geometry_type = geometry.geometry_type.clone();
+        let handle = mesh_assets.load(&key, queue.clone(), || GeometryInitHelper::create_mesh(&geometry_type));
+        mesh_assets.assign(entity, handle);
+        geometry.mesh = Some(handle);
+        log::debug!("Geometry {} now draws mesh {:?}, {} meshes loaded.", entity.id(), handle, mesh_assets.len());
     }
-}
\ No newline at end of file
+}
+
+// gives back the mesh references of geometries that were despawned or removed since the last frame
+pub fn MeshAssetCleanupSystem(
+    removed: RemovedComponents<GeometryComponent>,
+    mut mesh_assets: ResMut<MeshAssets>,
+){
+    for entity in removed.iter() {
+        mesh_assets.unassign(entity);
+    }
+}
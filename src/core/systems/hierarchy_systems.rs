@@ -0,0 +1,221 @@
+use bevy_ecs::{
+    prelude::{
+        Changed,
+        Commands,
+        Entity,
+        Or,
+        Query,
+        RemovedComponents,
+        Res,
+        With,
+    },
+    system::Command,
+    world::World,
+};
+
+use std::collections::HashMap;
+
+use cgmath::{
+    Matrix4,
+    SquareMatrix,
+};
+
+use crate::core::plugins::components::{
+    TransformComponent,
+    PreviousTransformComponent,
+    Parent,
+    Children,
+    GlobalTransform,
+};
+use crate::core::application::Time;
+
+use log;
+
+// attaches an entity to a new parent, or detaches it with None. the entity stays where it is in the world,
+// its transform is rewritten relative to the new parent
+pub struct SetParent{
+    pub child: Entity,
+    pub parent: Option<Entity>,
+}
+
+impl Command for SetParent{
+    fn write(self, world: &mut World){
+        set_parent(world, self.child, self.parent);
+    }
+}
+
+// current model to world, walking up through the parents. doesn't blend between ticks
+pub fn world_matrix(world: &World, entity: Entity) -> Matrix4<f32> {
+    let mut matrix = Matrix4::identity();
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        if let Some(transform) = world.get::<TransformComponent>(entity) {
+            matrix = transform.matrix() * matrix;
+        }
+        current = world.get::<Parent>(entity).map(|parent| parent.0);
+    }
+    matrix
+}
+
+fn is_ancestor(world: &World, ancestor: Entity, entity: Entity) -> bool {
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        if entity == ancestor {
+            return true;
+        }
+        current = world.get::<Parent>(entity).map(|parent| parent.0);
+    }
+    false
+}
+
+pub fn set_parent(world: &mut World, child: Entity, parent: Option<Entity>){
+    if world.get_entity(child).is_none() {
+        log::error!("Can't parent entity {}, it doesn't exist.", child.id());
+        return;
+    }
+    if let Some(parent) = parent {
+        if world.get_entity(parent).is_none() {
+            log::error!("Can't parent entity {} to {}, the parent doesn't exist.", child.id(), parent.id());
+            return;
+        }
+        if is_ancestor(world, child, parent) {
+            log::error!("Can't parent entity {} to {}, it would make a cycle.", child.id(), parent.id());
+            return;
+        }
+    }
+
+    // work out the local transform that keeps the child where it is before touching the hierarchy
+    let child_to_world = world_matrix(world, child);
+    let parent_to_world = parent.map_or(Matrix4::identity(), |parent| world_matrix(world, parent));
+    let local = parent_to_world.invert().map(|world_to_parent| TransformComponent::from_matrix(&(world_to_parent * child_to_world)));
+
+    if let Some(old_parent) = world.get::<Parent>(child).map(|parent| parent.0) {
+        if let Some(mut children) = world.get_mut::<Children>(old_parent) {
+            children.0.retain(|entity| *entity != child);
+        }
+    }
+
+    match parent {
+        Some(parent) => {
+            world.entity_mut(child).insert(Parent(parent));
+            match world.get_mut::<Children>(parent) {
+                Some(mut children) => children.0.push(child),
+                None => {
+                    world.entity_mut(parent).insert(Children(vec![child]));
+                },
+            }
+        },
+        None => {
+            world.entity_mut(child).remove::<Parent>();
+        },
+    }
+
+    match local {
+        Some(local) => {
+            world.entity_mut(child).insert(local);
+            // don't blend from where the old parent had it
+            world.entity_mut(child).insert(local.snapshot());
+        },
+        None => log::warn!("Parent of entity {} has a degenerate transform, keeping its local transform.", child.id()),
+    }
+}
+
+fn propagate(
+    commands: &mut Commands,
+    entity: Entity,
+    parent_to_world: Matrix4<f32>,
+    alpha: f32,
+    transforms: &mut Query<(Option<&TransformComponent>, Option<&PreviousTransformComponent>, Option<&mut GlobalTransform>)>,
+    children_query: &Query<&Children>,
+){
+    let model_to_world = match transforms.get_mut(entity) {
+        Ok((transform, previous_transform, global)) => {
+            // entities without a transform just sit on their parent
+            let model_to_world = match transform {
+                Some(transform) => parent_to_world * transform.interpolated(previous_transform, alpha).matrix(),
+                None => parent_to_world,
+            };
+            match global {
+                Some(mut global) => global.matrix = model_to_world,
+                None => {
+                    commands.entity(entity).insert(GlobalTransform::new(model_to_world));
+                },
+            }
+            model_to_world
+        },
+        Err(_) => return,
+    };
+
+    if let Ok(children) = children_query.get(entity) {
+        for child in children.0.iter() {
+            propagate(commands, *child, model_to_world, alpha, transforms, children_query);
+        }
+    }
+}
+
+// keeps Children lined up with Parent for entities that were given a Parent directly instead of through
+// SetParent, so propagation still reaches them. runs a stage ahead of propagation so any Children it has to
+// add are in place by then
+pub fn ParentSyncSystem(
+    mut commands: Commands,
+    changed: Query<(Entity, &Parent), Changed<Parent>>,
+    removed: RemovedComponents<Parent>,
+    parents: Query<&Parent>,
+    entities: Query<Entity>,
+    mut children_query: Query<(Entity, &mut Children)>,
+){
+    log::debug!("Running ParentSyncSystem...");
+    if changed.is_empty() && removed.iter().next().is_none() {
+        return;
+    }
+
+    // children that moved to another parent, were detached or were despawned come off the old list
+    for (entity, mut children) in children_query.iter_mut() {
+        let belongs = |child: &Entity| parents.get(*child).map_or(false, |parent| parent.0 == entity);
+        if !children.0.iter().all(belongs) {
+            children.0.retain(belongs);
+        }
+    }
+
+    let mut missing: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for (entity, parent) in changed.iter() {
+        match children_query.get_mut(parent.0) {
+            Ok((_, mut children)) => {
+                if !children.0.contains(&entity) {
+                    children.0.push(entity);
+                }
+            },
+            Err(_) => missing.entry(parent.0).or_default().push(entity),
+        }
+    }
+    for (parent, children) in missing {
+        if entities.get(parent).is_err() {
+            log::warn!("Entity {} is parented to {}, which doesn't exist.", children[0].id(), parent.id());
+            continue;
+        }
+        commands.entity(parent).insert(Children(children));
+    }
+}
+
+// works out every entity's GlobalTransform from the top of each hierarchy down. entities whose parent was
+// despawned are treated as roots. the top of a hierarchy doesn't need a transform of its own, a bare entity
+// with children still passes identity down to them
+pub fn TransformPropagationSystem(
+    mut commands: Commands,
+    time: Res<Time>,
+    roots: Query<(Entity, Option<&Parent>), Or<(With<TransformComponent>, With<Children>)>>,
+    mut transforms: Query<(Option<&TransformComponent>, Option<&PreviousTransformComponent>, Option<&mut GlobalTransform>)>,
+    children_query: Query<&Children>,
+){
+    log::debug!("Running TransformPropagationSystem...");
+    let roots = roots.iter()
+        .filter(|(_, parent)| match parent {
+            Some(parent) => transforms.get(parent.0).is_err(),
+            None => true,
+        })
+        .map(|(entity, _)| entity)
+        .collect::<Vec<Entity>>();
+    for entity in roots {
+        propagate(&mut commands, entity, Matrix4::identity(), time.interpolation, &mut transforms, &children_query);
+    }
+}
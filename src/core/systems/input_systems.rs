@@ -1,59 +1,152 @@
-use crate::core::plugins::components::{CameraComponent};
-use winit::event::VirtualKeyCode;
-use cgmath::InnerSpace;
+use crate::core::plugins::components::{
+    CameraComponent,
+    FlyCameraController,
+    OrbitCameraController,
+    PanZoomCameraController,
+};
+use cgmath::{
+    InnerSpace,
+    Vector3,
+};
+use winit::event::MouseButton;
 
-use crate::core::managers::input_manager::KeyInputQueue;
-use bevy_ecs::prelude::{Query, Res};
-// pub struct CameraMoveSystem;
+use crate::core::input::{
+    ActionState,
+    MouseState,
+    CursorGrab,
+};
+use crate::core::application::Time;
+use bevy_ecs::prelude::{Query, Res, ResMut};
 
-pub fn CameraMoveSystem(
-    mut query: Query<&mut CameraComponent>,
-    input_queue: Res<KeyInputQueue>
+// keeps pitch just shy of straight up or down so the view matrix doesn't flip
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+// unit vector for a yaw around z and a pitch up from the xy plane
+fn direction_from_angles(yaw: f32, pitch: f32) -> Vector3<f32> {
+    Vector3::new(pitch.cos() * yaw.cos(), pitch.cos() * yaw.sin(), pitch.sin())
+}
+
+// yaw and pitch of a direction, the inverse of direction_from_angles. a zero length direction has no angles,
+// so it's treated as looking down +y like an unrotated camera
+fn angles_from_direction(direction: Vector3<f32>) -> (f32, f32) {
+    if direction.magnitude2() < f32::EPSILON {
+        return (std::f32::consts::FRAC_PI_2, 0.0);
+    }
+    let direction = direction.normalize();
+    (direction.y.atan2(direction.x), direction.z.asin())
+}
+
+// these run every frame, so keyboard movement is scaled by how long the frame took. mouse motion and wheel
+// are already totals so they aren't.
+
+pub fn FlyCameraControllerSystem(
+    mut query: Query<(&mut CameraComponent, &FlyCameraController)>,
+    actions: Res<ActionState>,
+    mouse: Res<MouseState>,
+    cursor_grab: Res<CursorGrab>,
+    time: Res<Time>,
+) {
+    for (mut cam, controller) in query.iter_mut() {
+        let (mut yaw, mut pitch) = angles_from_direction(cam.look_at - cam.eye);
+        if cursor_grab.grabbed || mouse.pressed(MouseButton::Right) {
+            yaw -= mouse.motion[0] * controller.look_sensitivity;
+            pitch -= mouse.motion[1] * controller.look_sensitivity;
+            pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        }
+
+        let forward = direction_from_angles(yaw, pitch);
+        let right = forward.cross(cam.up).normalize();
+        let translation = (forward * actions.axis("move_forward"))
+            + (right * actions.axis("move_right"))
+            + (cam.up * actions.axis("move_up"));
+        cam.eye = cam.eye + (translation * (controller.move_speed * time.frame_delta));
+        cam.look_at = cam.eye + forward;
+    }
+}
+
+pub fn OrbitCameraControllerSystem(
+    mut query: Query<(&mut CameraComponent, &OrbitCameraController)>,
+    actions: Res<ActionState>,
+    mouse: Res<MouseState>,
+    time: Res<Time>,
+) {
+    for (mut cam, controller) in query.iter_mut() {
+        let offset = cam.eye - cam.look_at;
+        let mut distance = offset.magnitude();
+        let (mut yaw, mut pitch) = angles_from_direction(offset);
+
+        if mouse.pressed(MouseButton::Left) {
+            yaw -= mouse.motion[0] * controller.rotate_sensitivity;
+            pitch += mouse.motion[1] * controller.rotate_sensitivity;
+        }
+        yaw += actions.axis("orbit") * controller.rotate_speed * time.frame_delta;
+        pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+        let zoom = 1.0 - (mouse.wheel[1] * controller.zoom_speed);
+        if cam.is_orthographic() {
+            cam.zoom_orthographic(zoom);
+        } else {
+            distance = (distance * zoom).clamp(controller.min_distance, controller.max_distance);
+        }
+
+        cam.eye = cam.look_at + (direction_from_angles(yaw, pitch) * distance);
+    }
+}
+
+pub fn PanZoomCameraControllerSystem(
+    mut query: Query<(&mut CameraComponent, &PanZoomCameraController)>,
+    actions: Res<ActionState>,
+    mouse: Res<MouseState>,
+    time: Res<Time>,
 ) {
-    let mut input = input_queue.clone();
-    for mut cam in query.iter_mut() {
-        let mut forward = (cam.eye - cam.look_at).normalize();
-        forward.z = 0.0;
-        let mut right = forward.cross(cam.up).normalize();
-        right.z = 0.0;
-        let keys = input.drain(..);
-        let delta = 0.2;
-        for key in keys{
-            match key {
-                VirtualKeyCode::W => {
-                    cam.eye = cam.eye - (forward * delta);
-                    cam.look_at = cam.look_at - (forward * delta);
-                },
-                VirtualKeyCode::A => {
-                    cam.eye = cam.eye + (right * delta);
-                    cam.look_at = cam.look_at + (right * delta);
-                },
-                VirtualKeyCode::S => {
-                    cam.eye = cam.eye + (forward * delta);
-                    cam.look_at = cam.look_at + (forward * delta);
-                },
-                VirtualKeyCode::D => {
-                    cam.eye = cam.eye - (right * delta);
-                    cam.look_at = cam.look_at - (right * delta);
-                },
-                VirtualKeyCode::E => {
-                    cam.eye = cam.eye + (right * delta);
-                },
-                VirtualKeyCode::Q => {
-                    cam.eye = cam.eye - (right * delta);
-                },
-                VirtualKeyCode::F => {
-                    let dx = cam.up * delta;
-                    cam.eye = cam.eye + dx;
-                    cam.look_at = cam.look_at + dx;
-                },
-                VirtualKeyCode::R => {
-                    let dx = cam.up * delta;
-                    cam.eye = cam.eye - dx;
-                    cam.look_at = cam.look_at - dx;
-                },
-                _ => (),
+    for (mut cam, controller) in query.iter_mut() {
+        let offset = cam.eye - cam.look_at;
+        let distance = offset.magnitude();
+
+        // pan across the ground. looking straight down there's no ground direction to pan along
+        let flat_forward = Vector3::new(-offset.x, -offset.y, 0.0);
+        if flat_forward.magnitude2() > 1e-6 {
+            let forward = flat_forward.normalize();
+            let right = forward.cross(cam.up).normalize();
+            let mut pan = ((forward * actions.axis("move_forward")) + (right * actions.axis("move_right")))
+                * (controller.pan_speed * time.frame_delta);
+            if mouse.pressed(MouseButton::Middle) {
+                pan = pan + (((right * -mouse.motion[0]) + (forward * mouse.motion[1]))
+                    * (controller.drag_sensitivity * distance));
             }
+            cam.eye = cam.eye + pan;
+            cam.look_at = cam.look_at + pan;
         }
+        let rise = cam.up * (actions.axis("move_up") * controller.pan_speed * time.frame_delta);
+        cam.eye = cam.eye + rise;
+        cam.look_at = cam.look_at + rise;
+
+        // turn around look_at and zoom towards it
+        let (mut yaw, pitch) = angles_from_direction(cam.eye - cam.look_at);
+        yaw += actions.axis("orbit") * controller.rotate_speed * time.frame_delta;
+        let zoom = 1.0 - (mouse.wheel[1] * controller.zoom_speed);
+        let zoomed = if cam.is_orthographic() {
+            cam.zoom_orthographic(zoom);
+            distance
+        } else {
+            (distance * zoom).clamp(controller.min_distance, controller.max_distance)
+        };
+        cam.eye = cam.look_at + (direction_from_angles(yaw, pitch) * zoomed);
     }
 }
+
+// mouse motion and wheel are published once a tick but frames can run several times between ticks. the
+// controllers use them up on the first frame so the camera doesn't keep turning on the frames after
+pub fn MouseDeltaResetSystem(
+    mut mouse: ResMut<MouseState>,
+) {
+    mouse.motion = [0.0, 0.0];
+    mouse.wheel = [0.0, 0.0];
+}
+
+// same as the mouse, triggered actions count on the first frame after a tick and not the ones after it
+pub fn ActionTriggerResetSystem(
+    mut actions: ResMut<ActionState>,
+) {
+    actions.clear_triggered();
+}
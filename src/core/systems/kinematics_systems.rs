@@ -0,0 +1,55 @@
+use bevy_ecs::prelude::{
+    Query,
+    Res,
+};
+
+use cgmath::{
+    InnerSpace,
+    Quaternion,
+    Rad,
+    Rotation3,
+};
+
+use crate::core::plugins::components::{
+    TransformComponent,
+    VelocityComponent,
+};
+use crate::core::application::Time;
+
+use log;
+
+// integrates velocities into transforms once per tick. acceleration goes into the velocity first so the
+// move this tick already uses it
+pub fn KinematicsSystem(
+    mut query: Query<(&mut TransformComponent, &mut VelocityComponent)>,
+    time: Res<Time>,
+){
+    log::debug!("Running KinematicsSystem...");
+    let delta = time.delta;
+    for (mut transform, mut velocity) in query.iter_mut() {
+        let acceleration = velocity.acceleration;
+        velocity.linear += acceleration * delta;
+
+        if velocity.damping > 0.0 {
+            let keep = (1.0 - velocity.damping.min(1.0)).powf(delta);
+            velocity.linear *= keep;
+            velocity.angular *= keep;
+        }
+
+        if let Some(max_speed) = velocity.max_speed {
+            let speed = velocity.linear.magnitude();
+            if speed > max_speed && speed > 0.0 {
+                velocity.linear *= max_speed / speed;
+            }
+        }
+
+        let linear = velocity.linear;
+        transform.translate(linear * delta);
+
+        let angular_speed = velocity.angular.magnitude();
+        if angular_speed > 0.0 {
+            let axis = velocity.angular / angular_speed;
+            transform.rotate(Quaternion::from_axis_angle(axis, Rad(angular_speed * delta)));
+        }
+    }
+}
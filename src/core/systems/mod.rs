@@ -4,12 +4,28 @@ pub mod ui_systems;
 pub mod camera_init_system;
 pub mod terrain_systems;
 pub mod geometry_init;
+pub mod state_systems;
+pub mod transform_systems;
+pub mod picking_systems;
+pub mod hierarchy_systems;
+pub mod kinematics_systems;
+pub mod physics_systems;
 
 pub use render_systems::DirectionalLightingSystem;
 pub use render_systems::RequiresGraphicsPipeline;
 pub use render_systems::RenderableAssemblyStateModifierSystem;
 
 pub use geometry_init::GeometryInitializerSystem;
+pub use geometry_init::MeshAssetCleanupSystem;
+pub use transform_systems::TransformSnapshotSystem;
+pub use picking_systems::PickingSystem;
+pub use hierarchy_systems::TransformPropagationSystem;
+pub use hierarchy_systems::ParentSyncSystem;
+pub use hierarchy_systems::SetParent;
+pub use kinematics_systems::KinematicsSystem;
+pub use physics_systems::PhysicsSystem;
+pub use physics_systems::TerrainColliderSystem;
+pub use picking_systems::PickResult;
 
 pub use ui_systems::CameraUiSystem;
 pub use ui_systems::TransformUiSystem;
@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use bevy_ecs::prelude::{
+    Commands,
+    Entity,
+    Query,
+    Res,
+    ResMut,
+};
+
+use cgmath::{
+    InnerSpace,
+    Vector3,
+};
+
+use crate::core::plugins::components::{
+    Collider,
+    RigidBody,
+    TerrainComponent,
+    TransformComponent,
+    VelocityComponent,
+};
+use crate::core::physics::{
+    Contact,
+    Heightfield,
+    WorldShape,
+    collide,
+    sweep_and_prune,
+};
+use crate::core::application::Time;
+
+use log;
+
+// how much of an overlap gets corrected each tick, and how much is left alone so resting bodies don't jitter
+const CORRECTION_PERCENT: f32 = 0.8;
+const CORRECTION_SLOP: f32 = 0.01;
+// contacts closing slower than this don't bounce
+const RESTITUTION_THRESHOLD: f32 = 1.0;
+
+pub struct PhysicsSettings{
+    pub gravity: Vector3<f32>,
+    // passes over the contacts each tick. more is stiffer stacking
+    pub solver_iterations: u32,
+}
+
+impl Default for PhysicsSettings{
+    fn default() -> Self{
+        PhysicsSettings{
+            gravity: Vector3::new(0.0, 0.0, -9.81),
+            solver_iterations: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionEvent{
+    pub a: Entity,
+    pub b: Entity,
+    pub contact: Contact,
+    // the first tick these two touched
+    pub started: bool,
+}
+
+// every contact from the last physics step. systems that run after the physics stage see this tick's,
+// anything earlier sees the previous tick's
+#[derive(Default)]
+pub struct CollisionEvents{
+    events: Vec<CollisionEvent>,
+    touching: HashSet<(Entity, Entity)>,
+}
+
+impl CollisionEvents{
+    pub fn iter(&self) -> impl Iterator<Item = &CollisionEvent> {
+        self.events.iter()
+    }
+
+    // everything touching an entity, from that entity's side
+    pub fn involving(&self, entity: Entity) -> impl Iterator<Item = CollisionEvent> + '_ {
+        self.events.iter().filter_map(move |event| {
+            if event.a == entity {
+                Some(*event)
+            } else if event.b == entity {
+                Some(CollisionEvent{
+                    a: event.b,
+                    b: event.a,
+                    contact: Contact{
+                        normal: -event.contact.normal,
+                        ..event.contact
+                    },
+                    started: event.started,
+                })
+            } else {
+                None
+            }
+        })
+    }
+
+    fn publish(&mut self, contacts: &[(Entity, Entity, Contact)]){
+        let touching = contacts.iter().map(|(a, b, _)| (*a, *b)).collect::<HashSet<(Entity, Entity)>>();
+        self.events = contacts.iter()
+            .map(|(a, b, contact)| CollisionEvent{
+                a: *a,
+                b: *b,
+                contact: *contact,
+                started: !self.touching.contains(&(*a, *b)),
+            })
+            .collect();
+        self.touching = touching;
+    }
+}
+
+// what the solver needs from a body, worked on locally and written back once the step is done
+struct SolverBody{
+    entity: Entity,
+    inverse_mass: f32,
+    restitution: f32,
+    friction: f32,
+    velocity: Vector3<f32>,
+    correction: Vector3<f32>,
+}
+
+// one fixed step: gravity, then every overlapping pair is found, pushed apart and has its closing speed
+// turned around. impulses only change linear velocity, bodies don't pick up spin from collisions.
+// physics works with TransformComponent directly, so bodies are expected to be at the top of their hierarchy
+pub fn PhysicsSystem(
+    mut commands: Commands,
+    mut bodies: Query<(Entity, &mut TransformComponent, &Collider, Option<&RigidBody>, Option<&mut VelocityComponent>)>,
+    settings: Res<PhysicsSettings>,
+    time: Res<Time>,
+    mut events: ResMut<CollisionEvents>,
+){
+    log::debug!("Running PhysicsSystem...");
+    let delta = time.delta;
+
+    for (entity, _, _, body, velocity) in bodies.iter_mut() {
+        let body = match body {
+            Some(body) if body.is_dynamic() => body,
+            _ => continue,
+        };
+        match velocity {
+            Some(mut velocity) => velocity.linear += settings.gravity * (body.gravity_scale * delta),
+            None => {
+                commands.entity(entity).insert(VelocityComponent::default());
+            },
+        }
+    }
+
+    let (mut solver_bodies, contacts) = {
+        let mut solver_bodies = Vec::new();
+        let mut shapes = Vec::new();
+        for (entity, transform, collider, body, velocity) in bodies.iter() {
+            let shape = match WorldShape::pose(collider, transform) {
+                Some(shape) => shape,
+                None => continue,
+            };
+            shapes.push(shape);
+            solver_bodies.push(SolverBody{
+                entity: entity,
+                inverse_mass: body.map_or(0.0, |body| body.inverse_mass()),
+                restitution: body.map_or(0.0, |body| body.restitution),
+                friction: body.map_or(0.5, |body| body.friction),
+                velocity: velocity.map_or(Vector3::new(0.0, 0.0, 0.0), |velocity| velocity.linear),
+                correction: Vector3::new(0.0, 0.0, 0.0),
+            });
+        }
+
+        let boxes = shapes.iter().map(|shape| shape.aabb()).collect::<Vec<_>>();
+        let contacts = sweep_and_prune(&boxes).into_iter()
+            .filter_map(|(a, b)| collide(&shapes[a], &shapes[b]).map(|contact| (a, b, contact)))
+            .collect::<Vec<(usize, usize, Contact)>>();
+        (solver_bodies, contacts)
+    };
+
+    for _ in 0..settings.solver_iterations.max(1) {
+        for (a, b, contact) in contacts.iter() {
+            resolve_velocity(&mut solver_bodies, *a, *b, contact);
+        }
+    }
+    for (a, b, contact) in contacts.iter() {
+        correct_position(&mut solver_bodies, *a, *b, contact);
+    }
+
+    for solver_body in solver_bodies.iter().filter(|solver_body| solver_body.inverse_mass > 0.0) {
+        if let Ok((_, mut transform, _, _, velocity)) = bodies.get_mut(solver_body.entity) {
+            transform.translation += solver_body.correction;
+            if let Some(mut velocity) = velocity {
+                velocity.linear = solver_body.velocity;
+            }
+        }
+    }
+
+    let contacts = contacts.iter()
+        .map(|(a, b, contact)| (solver_bodies[*a].entity, solver_bodies[*b].entity, *contact))
+        .collect::<Vec<_>>();
+    events.publish(&contacts);
+}
+
+fn resolve_velocity(bodies: &mut [SolverBody], a: usize, b: usize, contact: &Contact){
+    let inverse_mass = bodies[a].inverse_mass + bodies[b].inverse_mass;
+    if inverse_mass <= 0.0 {
+        return;
+    }
+    let relative = bodies[b].velocity - bodies[a].velocity;
+    let closing = relative.dot(contact.normal);
+    // already separating
+    if closing >= 0.0 {
+        return;
+    }
+
+    let restitution = if -closing > RESTITUTION_THRESHOLD {
+        bodies[a].restitution.max(bodies[b].restitution)
+    } else {
+        0.0
+    };
+    let normal_impulse = -(1.0 + restitution) * closing / inverse_mass;
+    let mut impulse = contact.normal * normal_impulse;
+
+    // friction against the sliding part of the relative velocity, capped by how hard the bodies press together
+    let tangent = relative - contact.normal * closing;
+    if tangent.magnitude2() > f32::EPSILON {
+        let tangent_direction = tangent.normalize();
+        let friction = (bodies[a].friction * bodies[b].friction).sqrt();
+        let tangent_impulse = (-relative.dot(tangent_direction) / inverse_mass)
+            .clamp(-normal_impulse * friction, normal_impulse * friction);
+        impulse += tangent_direction * tangent_impulse;
+    }
+
+    let inverse_mass_a = bodies[a].inverse_mass;
+    let inverse_mass_b = bodies[b].inverse_mass;
+    bodies[a].velocity -= impulse * inverse_mass_a;
+    bodies[b].velocity += impulse * inverse_mass_b;
+}
+
+fn correct_position(bodies: &mut [SolverBody], a: usize, b: usize, contact: &Contact){
+    let inverse_mass = bodies[a].inverse_mass + bodies[b].inverse_mass;
+    if inverse_mass <= 0.0 {
+        return;
+    }
+    let correction = contact.normal * ((contact.depth - CORRECTION_SLOP).max(0.0) / inverse_mass * CORRECTION_PERCENT);
+    let inverse_mass_a = bodies[a].inverse_mass;
+    let inverse_mass_b = bodies[b].inverse_mass;
+    bodies[a].correction -= correction * inverse_mass_a;
+    bodies[b].correction += correction * inverse_mass_b;
+}
+
+// copies terrain heights into heightfield colliders whenever the terrain has been regenerated since the last copy
+pub fn TerrainColliderSystem(
+    mut query: Query<(&TerrainComponent, &mut Collider)>,
+){
+    log::debug!("Running TerrainColliderSystem...");
+    for (terrain, mut collider) in query.iter_mut() {
+        let geometry = terrain.geometry.lock().unwrap();
+        let stale = match &*collider {
+            Collider::Heightfield(field) => field.generation != geometry.generation,
+            _ => false,
+        };
+        if stale {
+            *collider = Collider::Heightfield(Heightfield::from_vertices(geometry.size, &geometry.vertices, geometry.generation));
+        }
+    }
+}
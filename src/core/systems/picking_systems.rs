@@ -0,0 +1,121 @@
+use bevy_ecs::prelude::{
+    Entity,
+    Query,
+    Res,
+    ResMut,
+};
+use winit::event::MouseButton;
+
+use cgmath::{
+    InnerSpace,
+    Matrix4,
+    SquareMatrix,
+    Vector3,
+};
+
+use crate::core::plugins::components::{
+    GeometryComponent,
+    TerrainComponent,
+    GlobalTransform,
+};
+use crate::core::rendering::geometries::{
+    BoundingVolume,
+    Ray,
+    Vertex,
+    Indices,
+};
+use crate::core::rendering::MeshAssets;
+use crate::core::systems::render_systems::CameraViews;
+use crate::core::input::MouseState;
+use crate::core::application::Time;
+
+use log;
+
+// the closest thing under the cursor
+#[derive(Debug, Clone, Copy)]
+pub struct PickHit{
+    pub entity: Entity,
+    // world space
+    pub point: Vector3<f32>,
+    pub distance: f32,
+}
+
+// what's under the cursor this frame, and what was under it when the left button went down
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PickResult{
+    pub hovered: Option<PickHit>,
+    pub clicked: Option<PickHit>,
+    // mouse state only changes once a tick but picking runs every frame, so a press stays visible for every
+    // frame drawn during its tick. this remembers which tick the last click was taken from
+    click_tick: Option<u64>,
+}
+
+// nearest hit along a world space ray against a model space triangle list. the bounds get checked first
+// so most misses never touch the triangles
+fn pick_triangles(ray: &Ray, model_to_world: &Matrix4<f32>, bounds: &BoundingVolume, vertices: &[Vertex], indices: &Indices) -> Option<f32> {
+    let world_bounds = bounds.transformed(model_to_world);
+    ray.intersect_aabb(world_bounds.min, world_bounds.max)?;
+    let world_to_model = model_to_world.invert()?;
+    // the model space ray keeps the same t, so it can be used on the world ray directly
+    ray.transformed(&world_to_model).intersect_mesh(vertices, indices)
+}
+
+// casts a world space ray against every geometry and terrain in the scene
+pub fn pick(
+    ray: &Ray,
+    geometry_query: &Query<(Entity, &GlobalTransform, &GeometryComponent)>,
+    terrain_query: &Query<(Entity, &GlobalTransform, &TerrainComponent)>,
+    mesh_assets: &MeshAssets,
+) -> Option<PickHit> {
+    let mut nearest: Option<(Entity, f32)> = None;
+    let mut consider = |entity: Entity, t: Option<f32>| {
+        if let Some(t) = t {
+            if nearest.map_or(true, |(_, nearest_t)| t < nearest_t) {
+                nearest = Some((entity, t));
+            }
+        }
+    };
+
+    for (entity, global, geometry) in geometry_query.iter() {
+        if let Some(mesh) = geometry.mesh.and_then(|handle| mesh_assets.get(handle)) {
+            let t = pick_triangles(ray, &global.matrix, &mesh.bounds, &mesh.vertices, &mesh.indices);
+            consider(entity, t);
+        }
+    }
+
+    for (entity, global, terrain) in terrain_query.iter() {
+        // terrain is a heightfield, so it's marched instead of tested triangle by triangle
+        let geometry = terrain.geometry.lock().unwrap();
+        let t = global.matrix.invert().and_then(|world_to_model| geometry.intersect_ray(&ray.transformed(&world_to_model)));
+        consider(entity, t);
+    }
+
+    nearest.map(|(entity, t)| {
+        let point = ray.at(t);
+        PickHit{
+            entity: entity,
+            point: point,
+            distance: (point - ray.origin).magnitude(),
+        }
+    })
+}
+
+pub fn PickingSystem(
+    geometry_query: Query<(Entity, &GlobalTransform, &GeometryComponent)>,
+    terrain_query: Query<(Entity, &GlobalTransform, &TerrainComponent)>,
+    mesh_assets: Res<MeshAssets>,
+    camera_views: Res<CameraViews>,
+    mouse: Res<MouseState>,
+    time: Res<Time>,
+    mut result: ResMut<PickResult>,
+){
+    log::debug!("Running picking system...");
+    let ray = mouse.position
+        .and_then(|cursor| camera_views.view_at(cursor).and_then(|view| view.ray_through(cursor)));
+
+    result.hovered = ray.and_then(|ray| pick(&ray, &geometry_query, &terrain_query, &mesh_assets));
+    if mouse.just_pressed(MouseButton::Left) && result.click_tick != Some(time.tick) {
+        result.clicked = result.hovered;
+        result.click_tick = Some(time.tick);
+    }
+}
@@ -3,6 +3,7 @@ use std::convert::TryInto;
 
 
 use bevy_ecs::prelude::{
+    Entity,
     Query,
     Res,
     ResMut,
@@ -12,14 +13,20 @@ use bevy_ecs::prelude::{
 use crate::core::plugins::components::{
     RenderableComponent,
     CameraComponent,
-    TransformComponent,
+    Projection,
+    ViewportRect,
+    GlobalTransform,
     DirectionalLightComponent,
     AmbientLightingComponent,
+    PointLightComponent,
     GeometryComponent,
 };
 use crate::core::rendering::geometries::geometry_primitives::{
     Vertex,
+    ScreenVertex,
 };
+use crate::core::rendering::geometries::Frustum;
+use crate::core::rendering::geometries::Ray;
 use crate::core::rendering::shaders;
 
 
@@ -27,14 +34,17 @@ use crate::core::managers::render_manager::{
     TriangleSecondaryBuffers,
     LightingSecondaryBuffers,
 };
-use crate::core::managers::input_manager::KeyInputQueue;
+use crate::core::input::ActionState;
 use crate::core::rendering::SceneState;
+use crate::core::rendering::MeshAssets;
 
+use cgmath::InnerSpace;
 use cgmath::Matrix4;
+use cgmath::SquareMatrix;
+use cgmath::Vector4;
 
 use vulkano::device::Device;
 use vulkano::device::Queue;
-use vulkano::swapchain::Surface;
 use vulkano::pipeline::GraphicsPipeline;
 use vulkano::pipeline::Pipeline;
 use vulkano::pipeline::PipelineBindPoint;
@@ -44,6 +54,7 @@ use vulkano::pipeline::PartialStateMode;
 use vulkano::pipeline::graphics::vertex_input::BuffersDefinition;
 use vulkano::pipeline::graphics::input_assembly::{InputAssemblyState, PrimitiveTopology};
 use vulkano::pipeline::graphics::viewport::ViewportState;
+use vulkano::pipeline::graphics::viewport::Viewport;
 use vulkano::pipeline::graphics::depth_stencil::DepthStencilState;
 use vulkano::pipeline::graphics::rasterization::{RasterizationState, CullMode, FrontFace};
 use vulkano::pipeline::graphics::color_blend::{
@@ -54,7 +65,6 @@ use vulkano::render_pass::Subpass;
 use vulkano::render_pass::RenderPass;
 use vulkano::buffer::CpuBufferPool;
 use vulkano::buffer::BufferUsage;
-use vulkano::buffer::CpuAccessibleBuffer;
 use vulkano::buffer::TypedBufferAccess;
 
 use vulkano::descriptor_set::PersistentDescriptorSet;
@@ -63,9 +73,6 @@ use vulkano::command_buffer::CommandBufferUsage;
 use vulkano::command_buffer::AutoCommandBufferBuilder;
 
 
-use winit::window::Window;
-use winit::event::ModifiersState;
-use winit::event::VirtualKeyCode;
 
 
 
@@ -89,22 +96,131 @@ pub fn RenderableInitializerSystem(
     }
 }
 
+// [view, perspective] of the main camera
 pub type CameraState = [Matrix4<f32>; 2];
+
+// everything a draw system needs to draw the world from one camera
+#[derive(Clone)]
+pub struct CameraView{
+    pub entity: Entity,
+    pub view: Matrix4<f32>,
+    pub perspective: Matrix4<f32>,
+    pub projection: Projection,
+    // where on screen the camera draws. each camera also gets its own slice of the depth range, higher
+    // priorities nearer, so cameras drawn over other cameras always win the depth test
+    pub viewport: Viewport,
+}
+
+impl CameraView{
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.perspective * self.view
+    }
+
+    // whether a point in window pixels lands inside this camera's viewport
+    pub fn contains(&self, cursor: [f32; 2]) -> bool {
+        let origin = self.viewport.origin;
+        let dimensions = self.viewport.dimensions;
+        cursor[0] >= origin[0] && cursor[0] < origin[0] + dimensions[0]
+            && cursor[1] >= origin[1] && cursor[1] < origin[1] + dimensions[1]
+    }
+
+    // world space ray from the near plane through a point in window pixels, with a normalized direction. the
+    // far plane is far enough out that unprojecting onto it loses all precision, so only the near point is used
+    pub fn ray_through(&self, cursor: [f32; 2]) -> Option<Ray> {
+        let screen_to_world = self.view_projection().invert()?;
+        let view_to_world = self.view.invert()?;
+        let origin = self.viewport.origin;
+        let dimensions = self.viewport.dimensions;
+        let x = ((cursor[0] - origin[0]) / dimensions[0]) * 2.0 - 1.0;
+        let y = ((cursor[1] - origin[1]) / dimensions[1]) * 2.0 - 1.0;
+
+        let unproject = |depth: f32| {
+            let point = screen_to_world * Vector4::new(x, y, depth, 1.0);
+            point.truncate() / point.w
+        };
+        let near = unproject(0.0);
+        let direction = match self.projection {
+            // from the eye out through the cursor
+            Projection::Perspective{..} => near - (view_to_world * Vector4::new(0.0, 0.0, 0.0, 1.0)).truncate(),
+            // every ray is parallel, straight down the camera's forward
+            Projection::Orthographic{..} => (view_to_world * Vector4::new(0.0, 0.0, -1.0, 0.0)).truncate(),
+        };
+        Some(Ray::new(near, direction.normalize()))
+    }
+}
+
+// every active camera this frame, lowest priority first. the last one is the main camera
+#[derive(Clone, Default)]
+pub struct CameraViews{
+    pub views: Vec<CameraView>,
+}
+
+impl CameraViews{
+    pub fn main(&self) -> Option<&CameraView> {
+        self.views.last()
+    }
+
+    // the highest priority camera drawing under a point in window pixels
+    pub fn view_at(&self, cursor: [f32; 2]) -> Option<&CameraView> {
+        self.views.iter().rev().find(|view| view.contains(cursor))
+    }
+}
+
+// how many draws the draw systems recorded and skipped this frame, summed over every camera
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CullingStats{
+    pub drawn: u32,
+    pub culled: u32,
+}
+
 pub fn CameraUpdateSystem(
-    mut query: Query<&mut CameraComponent>,
-    surface: Res<Arc<Surface<Window>>>,
+    mut query: Query<(Entity, &mut CameraComponent, Option<&GlobalTransform>)>,
+    scene_state: Res<Arc<SceneState>>,
     mut state: ResMut<CameraState>,
+    mut camera_views: ResMut<CameraViews>,
 ){
     log::debug!("Running camera update system...");
-    let dimensions: [u32; 2] = surface.window().inner_size().into();
-    let aspect = dimensions[0] as f32/ dimensions[1] as f32;
-    for mut camera in query.iter_mut(){
+    // the viewport matches whatever we're drawing into, window or offscreen image
+    let full_viewport = scene_state.viewport();
+    let mut cameras = query.iter_mut().filter(|(_, camera, _)| camera.active).collect::<Vec<_>>();
+    cameras.sort_by_key(|(entity, camera, _)| (camera.priority, entity.id()));
+
+    camera_views.views.clear();
+    let count = cameras.len() as f32;
+    for (i, (entity, mut camera, global)) in cameras.into_iter().enumerate() {
         log::debug!("updating camera");
-        camera.aspect = aspect;
-        camera.calculate_view();
+        let rect = camera.viewport.unwrap_or_else(ViewportRect::full);
+        let depth_start = (count - 1.0 - i as f32) / count;
+        let viewport = Viewport{
+            origin: [
+                full_viewport.origin[0] + rect.x * full_viewport.dimensions[0],
+                full_viewport.origin[1] + rect.y * full_viewport.dimensions[1],
+            ],
+            dimensions: [rect.width * full_viewport.dimensions[0], rect.height * full_viewport.dimensions[1]],
+            depth_range: depth_start..(depth_start + 1.0 / count),
+        };
 
-        // somehow make this unique idk
-        *state = [camera.get_view(), camera.get_perspective()];
+        let aspect = viewport.dimensions[0] / viewport.dimensions[1];
+        if camera.aspect != aspect {
+            camera.aspect = aspect;
+            camera.calculate_perspective();
+        }
+        match global {
+            Some(global) => camera.calculate_view_attached(&global.matrix),
+            None => camera.calculate_view(),
+        }
+
+        camera_views.views.push(CameraView{
+            entity: entity,
+            view: camera.get_view(),
+            perspective: camera.get_perspective(),
+            projection: camera.projection,
+            viewport: viewport,
+        });
+    }
+
+    if let Some(main) = camera_views.main() {
+        *state = [main.view, main.perspective];
     }
 }
 
@@ -151,82 +267,96 @@ impl RequiresGraphicsPipeline for RenderableDrawSystemPipeline{
 
 
 pub fn RenderableDrawSystem(
-    query: Query<(&TransformComponent, &GeometryComponent, With<RenderableComponent>)>,
-    camera_state: Res<CameraState>,
+    query: Query<(&GlobalTransform, &GeometryComponent, With<RenderableComponent>)>,
+    mesh_assets: Res<MeshAssets>,
+    camera_views: Res<CameraViews>,
     queue: Res<Arc<Queue>>,
     scene_state: Res<Arc<SceneState>>,
     mut buffer_vec: ResMut<TriangleSecondaryBuffers>,
+    mut culling_stats: ResMut<CullingStats>,
 ){
     log::debug!("Running RenderableDrawSystem...");
 
-    let viewport = scene_state.viewport();
     let pipeline: Arc<GraphicsPipeline> = scene_state.get_pipeline_for_system::<RenderableDrawSystemPipeline>().expect("Could not get pipeline from scene_state.");
 
     let layout = pipeline.layout().set_layouts().get(0).unwrap();
-    for (transform, geometry, _has_renderable) in query.iter() {
-        log::debug!("Creating secondary command buffer builder...");
-        // create buffer buildres
-        // create a command buffer builder
-        let mut builder = AutoCommandBufferBuilder::secondary_graphics(
-            queue.device().clone(),
-            queue.family(),
-            CommandBufferUsage::OneTimeSubmit,
-            pipeline.subpass().clone(),
-        )
-        .unwrap();
-        
-        log::debug!("Binding pipeline graphics for secondary command buffer....");
-        // this is the default color of the framebuffer
-        builder
-            .set_viewport(0, [viewport.clone()])
-            .bind_pipeline_graphics(pipeline.clone());
-
-        let uniform_buffer: CpuBufferPool::<shaders::triangle::vs::ty::Data> = CpuBufferPool::new(
-            queue.device().clone(),
-            BufferUsage::all()
-        );
-
-        // let g_arc = &renderable.geometry();
-        // let geometry = g_arc.lock().unwrap();
-        let uniform_buffer_subbuffer = {
-            // create matrix
-            let translation_matrix: Matrix4<f32> = Matrix4::from_translation(transform.global_position());
-            let rotation_matrix: Matrix4<f32> = transform.rotation();
-            let scale_matrix: Matrix4<f32> = Matrix4::from_scale(transform.scale());
-            let model_to_world: Matrix4<f32> = translation_matrix * rotation_matrix * scale_matrix;
-
-            
-            let uniform_buffer_data = shaders::triangle::vs::ty::Data{
-                mwv: (camera_state[1] * camera_state[0] * model_to_world).into()
+    for camera_view in camera_views.views.iter() {
+        let frustum = Frustum::from_view_projection(&camera_view.view_projection());
+        for (global, geometry, _has_renderable) in query.iter() {
+            let model_to_world: Matrix4<f32> = global.matrix;
+            // nothing to draw until the geometry has been given a mesh
+            let mesh = match geometry.mesh.and_then(|handle| mesh_assets.get(handle)) {
+                Some(mesh) => mesh,
+                None => continue,
             };
-            uniform_buffer.next(uniform_buffer_data).unwrap()
-        };
-
-        let set = PersistentDescriptorSet::new(
-            layout.clone(),
-            [WriteDescriptorSet::buffer(0, uniform_buffer_subbuffer)]
-        ).unwrap();
 
-        log::debug!("Building secondary commands...");
-        let _ = &builder
-            .bind_descriptor_sets(
-                PipelineBindPoint::Graphics,
-                pipeline.layout().clone(),
-                0,
-                set.clone(),
-            )
-            .bind_vertex_buffers(0, geometry.vertex_buffer().clone())
-            .bind_index_buffer(geometry.index_buffer().clone())
-            .draw_indexed(
-                (*geometry.index_buffer()).len() as u32,
-                1,
-                0,
-                0,
-                0
+            // skip anything this camera can't see
+            if !frustum.intersects(&mesh.bounds.transformed(&model_to_world)) {
+                culling_stats.culled += 1;
+                continue;
+            }
+            culling_stats.drawn += 1;
+
+            log::debug!("Creating secondary command buffer builder...");
+            // create buffer buildres
+            // create a command buffer builder
+            let mut builder = AutoCommandBufferBuilder::secondary_graphics(
+                queue.device().clone(),
+                queue.family(),
+                CommandBufferUsage::OneTimeSubmit,
+                pipeline.subpass().clone(),
             )
             .unwrap();
-        let command_buffer = builder.build().unwrap();
-        buffer_vec.buffers.push(Box::new(command_buffer));
+        
+            log::debug!("Binding pipeline graphics for secondary command buffer....");
+            // this is the default color of the framebuffer
+            builder
+                .set_viewport(0, [camera_view.viewport.clone()])
+                .bind_pipeline_graphics(pipeline.clone());
+
+            let uniform_buffer: CpuBufferPool::<shaders::triangle::vs::ty::Data> = CpuBufferPool::new(
+                queue.device().clone(),
+                BufferUsage::uniform_buffer()
+            );
+
+            // let g_arc = &renderable.geometry();
+            // let geometry = g_arc.lock().unwrap();
+            let uniform_buffer_subbuffer = {
+                let uniform_buffer_data = shaders::triangle::vs::ty::Data{
+                    mwv: (camera_view.view_projection() * model_to_world).into(),
+                    normal_matrix: global.normal_matrix().into(),
+                };
+                uniform_buffer.next(uniform_buffer_data).unwrap()
+            };
+
+            let set = PersistentDescriptorSet::new(
+                layout.clone(),
+                [WriteDescriptorSet::buffer(0, uniform_buffer_subbuffer)]
+            ).unwrap();
+
+            log::debug!("Building secondary commands...");
+            let index_buffer = &mesh.index_buffer;
+            builder
+                .bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    pipeline.layout().clone(),
+                    0,
+                    set.clone(),
+                )
+                .bind_vertex_buffers(0, mesh.vertex_buffer.clone());
+            let _ = index_buffer
+                .bind(&mut builder)
+                .draw_indexed(
+                    index_buffer.len() as u32,
+                    1,
+                    0,
+                    0,
+                    0
+                )
+                .unwrap();
+            let command_buffer = builder.build().unwrap();
+            buffer_vec.buffers.push(Box::new(command_buffer));
+        }
     }
 }
 
@@ -238,7 +368,7 @@ impl RequiresGraphicsPipeline for DirectionalLightingSystemPipeline{
         let fs = shaders::directional_lighting::fs::load(device.clone()).expect("failed to create fragment shader for directional lighting system.");
 
         GraphicsPipeline::start()
-            .vertex_input_state(BuffersDefinition::new().vertex::<Vertex>())
+            .vertex_input_state(BuffersDefinition::new().vertex::<ScreenVertex>())
             .vertex_shader(vs.entry_point("main").unwrap(), ())
             .input_assembly_state(InputAssemblyState::new())
             .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
@@ -261,7 +391,7 @@ impl RequiresGraphicsPipeline for DirectionalLightingSystemPipeline{
 
 
 pub fn DirectionalLightingSystem(
-    query: Query<&DirectionalLightComponent>,
+    query: Query<(&DirectionalLightComponent, Option<&GlobalTransform>)>,
     queue: Res<Arc<Queue>>,
     scene_state: Res<Arc<SceneState>>,
     mut buffer_vec: ResMut<LightingSecondaryBuffers>,
@@ -269,36 +399,7 @@ pub fn DirectionalLightingSystem(
     log::debug!("Running Directional Lighting System...");
 
     // v buffer
-    let vertex_buffer = {
-        CpuAccessibleBuffer::from_iter(
-            queue.device().clone(),
-            BufferUsage::all(),
-            false,
-            [
-                Vertex {
-                    position: [-1.0, -1.0, 0.0],
-                },
-                Vertex {
-                    position: [1.0, -1.0, 0.0],
-                },
-                Vertex {
-                    position: [1.0, 1.0, 0.0],
-                },
-                Vertex {
-                    position: [-1.0, -1.0, 0.0],
-                },
-                Vertex {
-                    position: [1.0, 1.0, 0.0],
-                },
-                Vertex {
-                    position: [-1.0, 1.0, 0.0],
-                },
-            ]
-            .iter()
-            .cloned(),
-        )
-        .expect("failed to create buffer")
-    };
+    let vertex_buffer = scene_state.fullscreen_quad();
     let color_input = scene_state.diffuse_buffer();
     let normals_input = scene_state.normals_buffer();
     let viewport = scene_state.viewport();
@@ -308,10 +409,12 @@ pub fn DirectionalLightingSystem(
     let subpass = Subpass::from(renderpass.clone(), 1).expect("Couldn't get lighting subpass in directional lighting system.");
     let layout = pipeline.layout().set_layouts().get(0).expect("Couldn't get pipeline layout.");
 
-    for light_comp in query.iter(){
+    for (light_comp, global) in query.iter(){
+        // attached lights turn with whatever they're attached to
+        let direction = global.map_or(light_comp.direction, |global| global.transform_vector(light_comp.direction));
         let push_constants = shaders::directional_lighting::fs::ty::PushConstants {
             color: [light_comp.color[0], light_comp.color[1], light_comp.color[2], 1.0],
-            direction: light_comp.direction.extend(0.0).into(),
+            direction: direction.extend(0.0).into(),
         };
 
         let descriptor_set = PersistentDescriptorSet::new(
@@ -370,7 +473,7 @@ impl RequiresGraphicsPipeline for AmbientLightingSystemPipeline{
         let fs = shaders::ambient_lighting::fs::load(device.clone()).expect("failed to create fragment shader for ambient lighting system.");
 
         GraphicsPipeline::start()
-        .vertex_input_state(BuffersDefinition::new().vertex::<Vertex>())
+        .vertex_input_state(BuffersDefinition::new().vertex::<ScreenVertex>())
         .vertex_shader(vs.entry_point("main").unwrap(), ())
         .input_assembly_state(InputAssemblyState::new())
         .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
@@ -401,36 +504,7 @@ pub fn AmbientLightingSystem(
     log::debug!("Running ambient Lighting System...");
 
     // v buffer
-    let vertex_buffer = {
-        CpuAccessibleBuffer::from_iter(
-            queue.device().clone(),
-            BufferUsage::all(),
-            false,
-            [
-                Vertex {
-                    position: [-1.0, -1.0, 0.0],
-                },
-                Vertex {
-                    position: [1.0, -1.0, 0.0],
-                },
-                Vertex {
-                    position: [1.0, 1.0, 0.0],
-                },
-                Vertex {
-                    position: [-1.0, -1.0, 0.0],
-                },
-                Vertex {
-                    position: [1.0, 1.0, 0.0],
-                },
-                Vertex {
-                    position: [-1.0, 1.0, 0.0],
-                },
-            ]
-            .iter()
-            .cloned(),
-        )
-        .expect("failed to create buffer")
-    };
+    let vertex_buffer = scene_state.fullscreen_quad();
     let color_input = scene_state.diffuse_buffer();
     let viewport = scene_state.viewport();
     let pipeline: Arc<GraphicsPipeline> = scene_state.get_pipeline_for_system::<AmbientLightingSystemPipeline>().expect("Could not get pipeline from scene_state.");
@@ -492,16 +566,135 @@ pub fn AmbientLightingSystem(
 }
 
 
+pub struct PointLightingSystemPipeline;
+impl RequiresGraphicsPipeline for PointLightingSystemPipeline{
+    fn create_graphics_pipeline(device: Arc<Device>, render_pass: Arc<RenderPass>) -> Arc<GraphicsPipeline>{
+
+        let vs = shaders::point_lighting::vs::load(device.clone()).expect("failed to create vertex shader for point lighting system.");
+        let fs = shaders::point_lighting::fs::load(device.clone()).expect("failed to create fragment shader for point lighting system.");
+
+        GraphicsPipeline::start()
+            .vertex_input_state(BuffersDefinition::new().vertex::<ScreenVertex>())
+            .vertex_shader(vs.entry_point("main").unwrap(), ())
+            .input_assembly_state(InputAssemblyState::new())
+            .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+            .fragment_shader(fs.entry_point("main").unwrap(), ())
+            .color_blend_state(ColorBlendState::new(Subpass::from(render_pass.clone(), 1).unwrap().num_color_attachments()).blend(
+                AttachmentBlend {
+                    color_op: BlendOp::Add,
+                    color_source: BlendFactor::One,
+                    color_destination: BlendFactor::One,
+                    alpha_op: BlendOp::Max,
+                    alpha_source: BlendFactor::One,
+                    alpha_destination: BlendFactor::One,
+                },
+            ))
+            .render_pass(Subpass::from(render_pass.clone(), 1).unwrap())
+            .build(device.clone())
+            .unwrap()
+    }
+}
+
+
+pub fn PointLightingSystem(
+    query: Query<(&PointLightComponent, Option<&GlobalTransform>)>,
+    camera_views: Res<CameraViews>,
+    queue: Res<Arc<Queue>>,
+    scene_state: Res<Arc<SceneState>>,
+    mut buffer_vec: ResMut<LightingSecondaryBuffers>,
+){
+    log::debug!("Running Point Lighting System...");
+
+    // v buffer
+    let vertex_buffer = scene_state.fullscreen_quad();
+    let color_input = scene_state.diffuse_buffer();
+    let normals_input = scene_state.normals_buffer();
+    let depth_input = scene_state.depth_buffer();
+    let pipeline: Arc<GraphicsPipeline> = scene_state.get_pipeline_for_system::<PointLightingSystemPipeline>().expect("Could not get pipeline from scene_state.");
+    let renderpass = scene_state.render_passes[0].clone();
+
+    let subpass = Subpass::from(renderpass.clone(), 1).expect("Couldn't get lighting subpass in point lighting system.");
+    let layout = pipeline.layout().set_layouts().get(0).expect("Couldn't get pipeline layout.");
+
+    // lit once per camera, each over its own part of the screen
+    for camera_view in camera_views.views.iter() {
+        // the fragment shader rebuilds each pixel's world position from its screen position and depth
+        let screen_to_world = match camera_view.view_projection().invert() {
+            Some(matrix) => matrix,
+            None => {
+                log::error!("Camera matrix can't be inverted, skipping point lights for it.");
+                continue;
+            }
+        };
+
+        for (light_comp, global) in query.iter(){
+            // attached lights are positioned relative to whatever they're attached to
+            let position = global.map_or(light_comp.position, |global| global.transform_point(light_comp.position));
+            let push_constants = shaders::point_lighting::fs::ty::PushConstants {
+                screen_to_world: screen_to_world.into(),
+                color: [light_comp.color[0], light_comp.color[1], light_comp.color[2], 1.0],
+                position: position.extend(light_comp.range).into(),
+                depth_range: [camera_view.viewport.depth_range.start, camera_view.viewport.depth_range.end, 0.0, 0.0],
+            };
+
+            let descriptor_set = PersistentDescriptorSet::new(
+                layout.clone(),
+                [
+                    WriteDescriptorSet::image_view(0, color_input.clone()),
+                    WriteDescriptorSet::image_view(1, normals_input.clone()),
+                    WriteDescriptorSet::image_view(2, depth_input.clone()),
+                ]
+            ).unwrap();
+
+            let mut builder = AutoCommandBufferBuilder::secondary_graphics(
+                queue.device().clone(),
+                queue.family(),
+                CommandBufferUsage::OneTimeSubmit,
+                subpass.clone()
+            )
+            .unwrap();
+
+            builder
+                .set_viewport(0, [camera_view.viewport.clone()])
+                .bind_pipeline_graphics(pipeline.clone())
+                .bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    pipeline.clone().layout().clone(),
+                    0,
+                    descriptor_set.clone(),
+                )
+                .push_constants(
+                    pipeline.layout().clone(),
+                    0,
+                    push_constants
+                )
+                .bind_vertex_buffers(
+                    0,
+                    vertex_buffer.clone(),
+                )
+                .draw(
+                    vertex_buffer.len().try_into().unwrap(),
+                    1,
+                    0,
+                    0
+                )
+                .unwrap();
+
+            // build and push 
+            let command_buffer = builder.build().expect("Failed to build secondary command buffer.");
+            buffer_vec.buffers.push(Box::new(command_buffer));
+        }
+    }
+}
+
+
 pub fn RenderableAssemblyStateModifierSystem(
     scene_state: Res<Arc<SceneState>>,
-    read_input: Res<KeyInputQueue>,
-    read_modifiers: Res<ModifiersState>,
+    actions: Res<ActionState>,
     device: Res<Arc<Device>>,
 ){
     log::debug!("Renderable wireframe sysetm...");
-    let input = read_input.clone();
-    let modifiers = read_modifiers.clone();
-    if modifiers.shift() && modifiers.alt() && input.contains(&VirtualKeyCode::Z){
+    if actions.triggered("toggle_wireframe"){
         let topology = match scene_state
             .get_pipeline_for_system::<RenderableDrawSystemPipeline>()
             .expect("Couldn't get pipeline for renderable draw in wireframe system.")
@@ -0,0 +1,44 @@
+use bevy_ecs::prelude::{
+    Res,
+    ResMut,
+};
+
+use crate::core::input::ActionState;
+use crate::core::application::application_states::{
+    StateTransitions,
+    ApplicationPlayState,
+    ApplicationPauseState,
+};
+
+// systems that run in application state schedules and request transitions between them
+
+pub fn EditorStateInputSystem(
+    actions: Res<ActionState>,
+    mut transitions: ResMut<StateTransitions>,
+){
+    if actions.triggered("toggle_play") {
+        log::info!("Entering play state...");
+        transitions.push(ApplicationPlayState::create());
+    }
+}
+
+pub fn PlayStateInputSystem(
+    actions: Res<ActionState>,
+    mut transitions: ResMut<StateTransitions>,
+){
+    if actions.triggered("pause") {
+        transitions.push(ApplicationPauseState::create());
+    } else if actions.triggered("toggle_play") {
+        log::info!("Leaving play state...");
+        transitions.pop();
+    }
+}
+
+pub fn PauseStateInputSystem(
+    actions: Res<ActionState>,
+    mut transitions: ResMut<StateTransitions>,
+){
+    if actions.triggered("pause") {
+        transitions.pop();
+    }
+}
@@ -8,20 +8,21 @@ use bevy_ecs::prelude::{
 use cgmath::Matrix4;
 
 use crate::core::plugins::components::TerrainComponent;
-use crate::core::plugins::components::TransformComponent;
+use crate::core::plugins::components::GlobalTransform;
 use crate::core::systems::RequiresGraphicsPipeline;
 use crate::core::rendering::shaders;
 use crate::core::rendering::geometries::Vertex;
 use crate::core::managers::render_manager::TriangleSecondaryBuffers;
 use crate::core::rendering::SceneState;
-use crate::core::systems::render_systems::CameraState;
-use crate::core::managers::input_manager::KeyInputQueue;
+use crate::core::systems::render_systems::CameraViews;
+use crate::core::systems::render_systems::CullingStats;
+use crate::core::rendering::geometries::Frustum;
+use crate::core::input::ActionState;
 use crate::core::systems::ui_systems::EguiState;
 use crate::core::plugins::components::TerrainUiComponent;
 
 use vulkano::buffer::CpuBufferPool;
 use vulkano::buffer::BufferUsage;
-use vulkano::buffer::TypedBufferAccess;
 use vulkano::command_buffer::CommandBufferUsage;
 use vulkano::command_buffer::AutoCommandBufferBuilder;
 use vulkano::device::Device;
@@ -41,21 +42,19 @@ use vulkano::pipeline::graphics::viewport::ViewportState;
 use vulkano::pipeline::graphics::depth_stencil::DepthStencilState;
 use vulkano::pipeline::PipelineBindPoint;
 
-use winit::event::VirtualKeyCode;
-use winit::event::ModifiersState;
 
 use std::sync::{Arc};
 
 pub fn TerrainInitSystem(
     mut query: Query<&mut TerrainComponent>,
-    device: Res<Arc<Device>>,
+    queue: Res<Arc<Queue>>,
 ){
     log::info!("Terrain init system...");
     for mut terrain in query.iter_mut() {
         {
             terrain.geometry.lock().unwrap().generate_terrain();
         }
-        terrain.initialize(device.clone());
+        terrain.initialize(queue.clone());
     }
 }
 
@@ -102,96 +101,101 @@ impl RequiresGraphicsPipeline for TerrainDrawSystemPipeline{
 
 
 pub fn TerrainDrawSystem(
-    query: Query<(&TransformComponent, &TerrainComponent)>,
-    camera_state: Res<CameraState>,
+    query: Query<(&GlobalTransform, &TerrainComponent)>,
+    camera_views: Res<CameraViews>,
     queue: Res<Arc<Queue>>,
     scene_state: Res<Arc<SceneState>>,
     mut buffer_vec: ResMut<TriangleSecondaryBuffers>,
+    mut culling_stats: ResMut<CullingStats>,
 ){
     log::debug!("Running Terrain Draw System...");
 
-    let viewport = scene_state.viewport();
     let pipeline: Arc<GraphicsPipeline> = scene_state.get_pipeline_for_system::<TerrainDrawSystemPipeline>().expect("Could not get pipeline from scene_state.");
 
     let layout = pipeline.layout().set_layouts().get(0).unwrap();
-    for (transform, terrain) in query.iter() {
-        log::debug!("Creating secondary command buffer builder...");
-        // create buffer buildres
-        // create a command buffer builder
-        let mut builder = AutoCommandBufferBuilder::secondary_graphics(
-            queue.device().clone(),
-            queue.family(),
-            CommandBufferUsage::OneTimeSubmit,
-            pipeline.subpass().clone(),
-        )
-        .unwrap();
-        
-        log::debug!("Binding pipeline graphics for secondary command buffer....");
-        // this is the default color of the framebuffer
-        builder
-            .set_viewport(0, [viewport.clone()])
-            .bind_pipeline_graphics(pipeline.clone());
-
-        let uniform_buffer: CpuBufferPool::<shaders::triangle::vs::ty::Data> = CpuBufferPool::new(
-            queue.device().clone(),
-            BufferUsage::all()
-        );
-
-        let g_arc = &terrain.geometry.clone();
-        let geometry = g_arc.lock().unwrap();
-        let uniform_buffer_subbuffer = {
-            // create matrix
-            let translation_matrix: Matrix4<f32> = Matrix4::from_translation(transform.global_position());
-            let rotation_matrix: Matrix4<f32> = transform.rotation();
-            let scale_matrix: Matrix4<f32> = Matrix4::from_scale(transform.scale());
-            let model_to_world: Matrix4<f32> = rotation_matrix * translation_matrix * scale_matrix;
-
-            
-            let uniform_buffer_data = shaders::triangle::vs::ty::Data{
-                mwv: (camera_state[1] * camera_state[0] * model_to_world).into()
-            };
-            uniform_buffer.next(uniform_buffer_data).unwrap()
-        };
+    for camera_view in camera_views.views.iter() {
+        let frustum = Frustum::from_view_projection(&camera_view.view_projection());
+        for (global, terrain) in query.iter() {
+            let g_arc = &terrain.geometry.clone();
+            let geometry = g_arc.lock().unwrap();
+            let model_to_world: Matrix4<f32> = global.matrix;
 
-        let set = PersistentDescriptorSet::new(
-            layout.clone(),
-            [WriteDescriptorSet::buffer(0, uniform_buffer_subbuffer)]
-        ).unwrap();
-
-        log::debug!("Building secondary commands...");
-        let _ = &builder
-            .bind_descriptor_sets(
-                PipelineBindPoint::Graphics,
-                pipeline.layout().clone(),
-                0,
-                set.clone(),
-            )
-            .bind_vertex_buffers(0, geometry.vertex_buffer.clone().unwrap().clone())
-            .bind_index_buffer(geometry.index_buffer.clone().unwrap().clone())
-            .draw_indexed(
-                (*geometry.index_buffer.clone().unwrap()).len() as u32,
-                1,
-                0,
-                0,
-                0
+            // skip anything this camera can't see
+            if !frustum.intersects(&geometry.bounds.transformed(&model_to_world)) {
+                culling_stats.culled += 1;
+                continue;
+            }
+            culling_stats.drawn += 1;
+
+            log::debug!("Creating secondary command buffer builder...");
+            // create buffer buildres
+            // create a command buffer builder
+            let mut builder = AutoCommandBufferBuilder::secondary_graphics(
+                queue.device().clone(),
+                queue.family(),
+                CommandBufferUsage::OneTimeSubmit,
+                pipeline.subpass().clone(),
             )
             .unwrap();
-        let command_buffer = builder.build().unwrap();
-        buffer_vec.buffers.push(Box::new(command_buffer));
+        
+            log::debug!("Binding pipeline graphics for secondary command buffer....");
+            // this is the default color of the framebuffer
+            builder
+                .set_viewport(0, [camera_view.viewport.clone()])
+                .bind_pipeline_graphics(pipeline.clone());
+
+            let uniform_buffer: CpuBufferPool::<shaders::triangle::vs::ty::Data> = CpuBufferPool::new(
+                queue.device().clone(),
+                BufferUsage::uniform_buffer()
+            );
+
+            let uniform_buffer_subbuffer = {
+                let uniform_buffer_data = shaders::triangle::vs::ty::Data{
+                    mwv: (camera_view.view_projection() * model_to_world).into(),
+                    normal_matrix: global.normal_matrix().into(),
+                };
+                uniform_buffer.next(uniform_buffer_data).unwrap()
+            };
+
+            let set = PersistentDescriptorSet::new(
+                layout.clone(),
+                [WriteDescriptorSet::buffer(0, uniform_buffer_subbuffer)]
+            ).unwrap();
+
+            log::debug!("Building secondary commands...");
+            let index_buffer = geometry.index_buffer.clone().unwrap();
+            builder
+                .bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    pipeline.layout().clone(),
+                    0,
+                    set.clone(),
+                )
+                .bind_vertex_buffers(0, geometry.vertex_buffer.clone().unwrap().clone());
+            let _ = index_buffer
+                .bind(&mut builder)
+                .draw_indexed(
+                    index_buffer.len() as u32,
+                    1,
+                    0,
+                    0,
+                    0
+                )
+                .unwrap();
+            let command_buffer = builder.build().unwrap();
+            buffer_vec.buffers.push(Box::new(command_buffer));
+        }
     }
 }
 
 
 pub fn TerrainAssemblyStateModifierSystem(
     scene_state: Res<Arc<SceneState>>,
-    read_input: Res<KeyInputQueue>,
-    read_modifiers: Res<ModifiersState>,
+    actions: Res<ActionState>,
     device: Res<Arc<Device>>,
 ){
     log::debug!("Terrain wireframe system...");
-    let input = read_input.clone();
-    let modifiers = read_modifiers.clone();
-    if modifiers.shift() && modifiers.alt() && input.contains(&VirtualKeyCode::Z){
+    if actions.triggered("toggle_wireframe"){
         let topology = match scene_state
             .get_pipeline_for_system::<TerrainDrawSystemPipeline>()
             .expect("Couldn't get pipeline for renderable draw in wireframe system.")
@@ -249,34 +253,43 @@ impl TerrainAssemblyStateSystemPipeline {
 }
 
 
+// past 256 a side the terrain needs 32 bit indices. every change regenerates and uploads the whole grid while
+// the frame waits, so the slider stops well short of sizes that would take seconds
+const MAX_TERRAIN_SIZE: usize = 512;
+
 pub fn TerrainUiSystem(
     mut query: Query<&mut TerrainComponent, With<TerrainUiComponent>>,
     egui_state: Res<EguiState>,
+    queue: Res<Arc<Queue>>,
 ){
     log::debug!("Terrain ui system...");
 
     let ctx = egui_state.ctx.clone();
     for terrain in query.iter_mut(){
-        let mut size = terrain.get_size();
-        let mut amplitude = {
-            terrain.geometry.lock().expect("Cannot get terrain in terrain ui system.").amplitude.clone()
+        let (current_size, current_amplitude) = {
+            let geometry = terrain.geometry.lock().expect("Cannot get terrain in terrain ui system.");
+            (geometry.size, geometry.amplitude)
         };
+        let mut size = current_size;
+        let mut amplitude = current_amplitude;
 
         egui::Window::new("Terrain Settings")
             .show(&ctx, |ui| {
                 ui.horizontal(|ui|{
                     ui.label("Size");
-                    ui.add(egui::Slider::new(&mut size, 2..=100).step_by(1.0));
+                    ui.add(egui::Slider::new(&mut size, 2..=MAX_TERRAIN_SIZE).step_by(1.0));
                 });
                 ui.horizontal(|ui|{
                     ui.label("Amplidutde");
                     ui.add(egui::Slider::new(&mut amplitude, 0.1..=50.0).step_by(0.1));
                 });
             });
-        if size < 1 {
-            size = 1;
+        if size == current_size && amplitude == current_amplitude {
+            continue;
         }
-        terrain.set_size(size as usize);
-        terrain.geometry.lock().expect("Cannot get terrain in terrain ui system.").amplitude = amplitude;
+        let mut geometry = terrain.geometry.lock().expect("Cannot get terrain in terrain ui system.");
+        geometry.amplitude = amplitude;
+        geometry.set_size(size);
+        geometry.initialize(queue.clone());
     }
-}
\ No newline at end of file
+}
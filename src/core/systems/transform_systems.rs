@@ -0,0 +1,27 @@
+use bevy_ecs::prelude::{
+    Commands,
+    Entity,
+    Query,
+};
+
+use crate::core::plugins::components::{
+    TransformComponent,
+    PreviousTransformComponent,
+};
+
+// runs at the start of every tick so render systems can interpolate from where things were to where the
+// tick moved them. entities that don't have a snapshot yet get one
+pub fn TransformSnapshotSystem(
+    mut commands: Commands,
+    mut query: Query<(Entity, &TransformComponent, Option<&mut PreviousTransformComponent>)>,
+){
+    log::debug!("Running TransformSnapshotSystem...");
+    for (entity, transform, previous) in query.iter_mut() {
+        match previous {
+            Some(mut previous) => *previous = transform.snapshot(),
+            None => {
+                commands.entity(entity).insert(transform.snapshot());
+            },
+        }
+    }
+}
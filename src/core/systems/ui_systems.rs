@@ -1,16 +1,28 @@
-use crate::core::plugins::components::{DebugUiComponent, CameraComponent, TransformComponent, TransformUiComponent};
+use crate::core::plugins::components::{DebugUiComponent, CameraComponent, Projection, TransformComponent, TransformUiComponent};
+use crate::core::systems::render_systems::CullingStats;
+use crate::core::application::application_states::{
+    StateTransitions,
+    ApplicationEditorState,
+    ApplicationPlayState,
+    ApplicationPauseState,
+};
 // use egui_winit::State;
 use egui_vulkano::Painter;
 use egui::Context;
 
 
 use bevy_ecs::prelude::{
+    Entity,
     Res,
     ResMut,
     Query,
     With,
 };
 // use puffin_egui;
+use cgmath::{
+    Deg,
+    Euler,
+};
 
 use log;
 
@@ -24,6 +36,8 @@ pub fn DebugUiSystem(
     mut query: Query<&mut DebugUiComponent>,
     egui_state: Res<EguiState>,
     mut should_save: ResMut<bool>,
+    mut transitions: ResMut<StateTransitions>,
+    culling_stats: Res<CullingStats>,
 ){
     log::debug!("Debug ui...");
     let ctx = egui_state.ctx.clone();
@@ -49,6 +63,21 @@ pub fn DebugUiSystem(
                         }
                     });
 
+                    ui.menu_button("State", |ui| {
+                        if ui.button("Editor").clicked() {
+                            transitions.push(ApplicationEditorState::create());
+                        }
+                        if ui.button("Play").clicked() {
+                            transitions.push(ApplicationPlayState::create());
+                        }
+                        if ui.button("Pause").clicked() {
+                            transitions.push(ApplicationPauseState::create());
+                        }
+                        if ui.button("Back").clicked() {
+                            transitions.pop();
+                        }
+                    });
+
                     ui.menu_button("Debug Options", |ui| {
                         if ui.button("Toggle Profiling").clicked() {
                             log::info!("I still don't know why this breaks.");
@@ -59,52 +88,99 @@ pub fn DebugUiSystem(
                             comp.terrain_wireframe = !comp.terrain_wireframe;
                         }
                     });
+
+                    ui.label(format!("Drawn: {} Culled: {}", culling_stats.drawn, culling_stats.culled));
                 });
             }); // end of panel
     }
 }
 
 pub fn CameraUiSystem(
-    mut query: Query<&mut CameraComponent>,
+    mut query: Query<(Entity, &mut CameraComponent)>,
     egui_state: Res<EguiState>,
 )
 {
     log::debug!("Camera ui...");
     let ctx = egui_state.ctx.clone();
-    for mut cam in query.iter_mut(){
-        let mut fov = cam.fov;
-        egui::Window::new("Camera Settings")
+    for (entity, mut cam) in query.iter_mut(){
+        let mut projection = cam.projection;
+        let mut active = cam.active;
+        let mut priority = cam.priority;
+        // one window per camera, titles have to be unique
+        egui::Window::new(format!("Camera Settings {}", entity.id()))
             .show(&ctx, |ui| {
-                ui.label("FOV");
-                ui.add(egui::Slider::new(&mut fov, 0.1..=3.0))
+                ui.checkbox(&mut active, "Active");
+                ui.horizontal(|ui| {
+                    ui.label("Priority");
+                    ui.add(egui::DragValue::new(&mut priority));
+                });
+                ui.horizontal(|ui| {
+                    let perspective = matches!(projection, Projection::Perspective{..});
+                    if ui.radio(perspective, "Perspective").clicked() && !perspective {
+                        projection = Projection::default();
+                    }
+                    if ui.radio(!perspective, "Orthographic").clicked() && perspective {
+                        projection = Projection::Orthographic{size: 20.0};
+                    }
+                });
+                match &mut projection {
+                    Projection::Perspective{fov} => {
+                        ui.label("FOV");
+                        ui.add(egui::Slider::new(fov, 0.1..=3.0));
+                    },
+                    Projection::Orthographic{size} => {
+                        ui.label("Size");
+                        ui.add(egui::Slider::new(size, 0.1..=200.0));
+                    },
+                }
             });
-        if cam.fov != fov {
-            cam.fov = fov;
-            cam.calculate_perspective();
+        if cam.projection != projection {
+            cam.set_projection(projection);
         }
+        cam.active = active;
+        cam.priority = priority;
     }
 }
 
 
 pub fn TransformUiSystem(
-    mut query: Query<&mut TransformComponent, With<TransformUiComponent>>,
+    mut query: Query<(Entity, &mut TransformComponent), With<TransformUiComponent>>,
     egui_state: Res<EguiState>,
 ){
     log::debug!("Transform ui....");
     let ctx = egui_state.ctx.clone();
-    for _transform in query.iter_mut(){
-        // let mut pos = transform.global_position();
-        // let mut posx = pos[0];
-        // let mut posy = pos[1];
-        // let mut posz = pos[2];
-        egui::Window::new("Transform")
+    for (entity, mut transform) in query.iter_mut(){
+        let mut edited = *transform;
+        let euler = transform.euler_angles();
+        let mut angles = [Deg::from(euler.x).0, Deg::from(euler.y).0, Deg::from(euler.z).0];
+        let original_angles = angles;
+        egui::Window::new(format!("Transform {}", entity.id()))
             .show(&ctx, |ui| {
                 ui.horizontal(|ui| {
                     ui.label("Position");
-                    // ui.add(egui::DragValue::new(&mut posx).speed(0.1).clamp_range(-100.0..=100.0));
-                    // ui.add(egui::DragValue::new(&mut posy).speed(0.1).clamp_range(-100.0..=100.0));
-                    // ui.add(egui::DragValue::new(&mut posz).speed(0.1).clamp_range(-100.0..=100.0));
+                    ui.add(egui::DragValue::new(&mut edited.translation.x).speed(0.1));
+                    ui.add(egui::DragValue::new(&mut edited.translation.y).speed(0.1));
+                    ui.add(egui::DragValue::new(&mut edited.translation.z).speed(0.1));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Rotation");
+                    for angle in angles.iter_mut() {
+                        ui.add(egui::DragValue::new(angle).speed(1.0).suffix("°"));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Scale");
+                    ui.add(egui::DragValue::new(&mut edited.scale.x).speed(0.01));
+                    ui.add(egui::DragValue::new(&mut edited.scale.y).speed(0.01));
+                    ui.add(egui::DragValue::new(&mut edited.scale.z).speed(0.01));
                 });
             });
+        // only go through euler angles when they were touched, the round trip isn't exact
+        if angles != original_angles {
+            edited.set_euler_angles(Euler::new(Deg(angles[0]).into(), Deg(angles[1]).into(), Deg(angles[2]).into()));
+        }
+        if edited != *transform {
+            *transform = edited;
+        }
     }
-}
\ No newline at end of file
+}
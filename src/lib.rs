@@ -6,3 +6,4 @@ pub use crate::core::application::application_manager::Application;
 
 // importing traits i guess
 pub use crate::core::managers::manager::Manager;
+pub use crate::core::plugins::Plugin;